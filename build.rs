@@ -2,23 +2,45 @@ use anyhow::Result;
 
 #[path = "build/assets.rs"]
 mod assets;
+#[path = "build/cache.rs"]
+mod cache;
+#[path = "src/cards.rs"]
+mod cards;
+#[path = "src/config.rs"]
+mod config;
+#[path = "build/content.rs"]
+mod content;
+#[path = "build/diagnostics.rs"]
+mod diagnostics;
+#[path = "build/feed.rs"]
+mod feed;
 #[path = "build/fonts.rs"]
 mod fonts;
 #[path = "src/frontmatter.rs"]
 mod frontmatter;
+#[path = "build/gfm.rs"]
+mod gfm;
+#[path = "build/headings.rs"]
+mod headings;
+#[path = "src/images.rs"]
+mod images;
 #[path = "build/markdown.rs"]
 mod markdown;
+#[path = "build/og.rs"]
+mod og;
 #[path = "build/posts.rs"]
 mod posts;
+#[path = "build/resolver.rs"]
+mod resolver;
+#[path = "build/responsive.rs"]
+mod responsive;
 #[path = "build/sitemap.rs"]
 mod sitemap;
 
 const PREAMBLE_PATH: &str = "static/preamble.typ";
 const GENERATED_DIR: &str = "static/generated";
 const GENERATED_MD_DIR: &str = "static/generated/md";
-const PANDOC_FILTER: &str = "scripts/pandoc/html-to-md.lua";
 const MARKDOWN_ENV_KEY: &str = "RODIN_MARKDOWN_ENABLED";
-const SITE_URL: &str = "https://suzuneu.com";
 const SITEMAP_PATH: &str = "static/generated/sitemap.xml";
 
 fn main() -> Result<()> {
@@ -42,37 +64,54 @@ fn main() -> Result<()> {
         .unwrap_or_else(|| "unknown".to_string());
     println!("cargo:rustc-env=GIT_HASH={}", git_hash);
 
-    println!("cargo:rerun-if-changed=src");
+    // build.rs が読む src 側のファイルだけ（fonts.rs は自分で登録する）
+    println!("cargo:rerun-if-changed=src/cards.rs");
+    println!("cargo:rerun-if-changed=src/config.rs");
+    println!("cargo:rerun-if-changed=src/frontmatter.rs");
     println!("cargo:rerun-if-changed=content");
     println!("cargo:rerun-if-changed=static/app.js");
     println!("cargo:rerun-if-changed=static/home.js");
     println!("cargo:rerun-if-changed=static/css");
+    println!("cargo:rerun-if-changed=static/images");
     println!("cargo:rerun-if-changed={PREAMBLE_PATH}");
-    println!("cargo:rerun-if-changed={PANDOC_FILTER}");
+    println!("cargo:rerun-if-changed={}", og::OG_TEMPLATE_PATH);
+    println!("cargo:rerun-if-changed={}", posts::PRINT_TEMPLATE_PATH);
+    println!("cargo:rerun-if-env-changed={}", content::SLUG_STYLE_ENV_KEY);
+    println!("cargo:rerun-if-env-changed={}", config::CONFIG_PATH_ENV_KEY);
+    println!("cargo:rerun-if-env-changed={}", config::SITE_URL_ENV_KEY);
 
-    println!("cargo:rustc-env={MARKDOWN_ENV_KEY}=false");
+    let config = config::SiteConfig::load()?;
+    let site = &config.site;
+    println!("cargo:rerun-if-changed={}", config::config_path().display());
+
+    diagnostics::set_format(diagnostics::DiagnosticFormat::Cargo);
 
     // フォントを先に生成してから、アセット処理（ハッシュ化含む）を行う
-    fonts::subset_regular_font()?;
+    fonts::subset_regular_font(&site.name)?;
     assets::minify_assets()?;
 
-    let mut metas = posts::build_posts(PREAMBLE_PATH, GENERATED_DIR)?;
-    let markdown_ok = markdown::build_markdown(&mut metas, GENERATED_MD_DIR, PANDOC_FILTER)?;
-    if markdown_ok {
-        println!("cargo:rustc-env={MARKDOWN_ENV_KEY}=true");
-    } else {
-        // keep index consistent
-        for m in metas.iter_mut() {
-            m.markdown = None;
-        }
-    }
+    // 全ページのコンパイルで画像などの読み込みを使い回す
+    let assets = resolver::AssetResolver::new(resolver::ASSET_ROOT);
+    let mut metas = posts::build_posts(PREAMBLE_PATH, GENERATED_DIR, &assets, site)?;
+    markdown::build_markdown(&mut metas, GENERATED_MD_DIR, &site.url)?;
+    println!("cargo:rustc-env={MARKDOWN_ENV_KEY}=true");
     markdown::write_index(&metas, GENERATED_DIR)?;
     // index.json を読むのでこの順
-    posts::build_home(PREAMBLE_PATH, GENERATED_DIR)?;
-    posts::build_profile(PREAMBLE_PATH, GENERATED_DIR)?;
-    let pgp_meta = posts::build_pgp(PREAMBLE_PATH, GENERATED_DIR)?;
+    posts::build_home(PREAMBLE_PATH, GENERATED_DIR, &assets, site)?;
+    posts::build_profile(PREAMBLE_PATH, GENERATED_DIR, &assets, site)?;
+    let pgp_meta = posts::build_pgp(PREAMBLE_PATH, GENERATED_DIR, &assets, site)?;
     let pgp_meta_ref = pgp_meta.as_ref();
-    sitemap::write_sitemap(&metas, pgp_meta_ref, SITE_URL, SITEMAP_PATH)?;
+    // 限定公開・予約投稿はサイトマップとフィードに載せない
+    let listed = posts::listed_posts(&metas);
+    let urls = sitemap::write_sitemap(&listed, pgp_meta_ref, &site.url, SITEMAP_PATH)?;
+    println!("cargo:warning=generated sitemap with {urls} urls");
+    let (entries, tags) = feed::write_feeds(&listed, site, GENERATED_DIR)?;
+    println!("cargo:warning=generated feeds with {entries} entries ({tags} tags)");
+
+    let warnings = diagnostics::warning_count();
+    if warnings > 0 {
+        println!("cargo:warning={warnings} Typst warning(s); run `rodin-content` for details");
+    }
     Ok(())
 }
 
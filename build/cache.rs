@@ -0,0 +1,130 @@
+use crate::{diagnostics::Diagnostic, frontmatter::FrontMatter, resolver::AssetResolver};
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+    process::Command,
+    sync::LazyLock,
+};
+
+/// キャッシュの形式を変えたら上げる
+const CACHE_VERSION: u32 = 4;
+const CACHE_FILE: &str = "build-cache.json";
+
+static STRING_LITERAL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""([^"\n]+)""#).expect("valid regex"));
+
+/// 記事ごとのコンパイル結果キャッシュ（static/generated/build-cache.json）
+///
+/// `key` はプリアンブルとツールチェーンのハッシュで、変わったら全記事を作り直す。
+#[derive(Default, Serialize, Deserialize)]
+pub struct BuildCache {
+    key: String,
+    posts: HashMap<String, CachedPost>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CachedPost {
+    pub source_hash: String,
+    /// 参照しているバイナリアセットのパスとハッシュ
+    pub assets: BTreeMap<String, String>,
+    pub meta: FrontMatter,
+    /// コンパイル時の Typst の警告（再利用時に出し直す）
+    #[serde(default)]
+    pub warnings: Vec<Diagnostic>,
+}
+
+impl BuildCache {
+    pub fn load(generated_dir: &str, preamble: &str) -> Self {
+        let key = cache_key(preamble);
+        let path = Path::new(generated_dir).join(CACHE_FILE);
+        let cached = fs::read(&path)
+            .ok()
+            .and_then(|b| serde_json::from_slice::<BuildCache>(&b).ok());
+        match cached {
+            Some(cache) if cache.key == key => cache,
+            Some(_) => {
+                println!("cargo:warning=preamble or toolchain changed; rebuilding all posts");
+                Self {
+                    key,
+                    ..Default::default()
+                }
+            }
+            None => Self {
+                key,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// ソースと参照アセットが変わっておらず、出力 HTML も残っていれば前回の結果を返す
+    pub fn lookup(
+        &self,
+        slug: &str,
+        source_hash: &str,
+        assets: &BTreeMap<String, String>,
+    ) -> Option<&CachedPost> {
+        let cached = self.posts.get(slug)?;
+        if cached.source_hash != source_hash || &cached.assets != assets {
+            return None;
+        }
+        cached.meta.html_file().exists().then_some(cached)
+    }
+
+    /// 今回のビルド結果で置き換えて保存する（消えた記事はここで落ちる）
+    pub fn save(mut self, generated_dir: &str, posts: HashMap<String, CachedPost>) -> Result<()> {
+        self.posts = posts;
+        let path = Path::new(generated_dir).join(CACHE_FILE);
+        fs::write(path, serde_json::to_string(&self)?)?;
+        Ok(())
+    }
+}
+
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// ソース中の文字列リテラルのうち、アセットとして解決できるものをハッシュ付きで返す
+pub fn referenced_assets(source: &str, assets: &AssetResolver) -> BTreeMap<String, String> {
+    STRING_LITERAL_RE
+        .captures_iter(source)
+        .filter_map(|c| c.get(1))
+        .filter_map(|m| {
+            assets
+                .read(m.as_str())
+                .map(|bytes| (m.as_str().to_string(), hash_bytes(&bytes)))
+        })
+        .collect()
+}
+
+fn cache_key(preamble: &str) -> String {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = Command::new(rustc)
+        .arg("-V")
+        .output()
+        .ok()
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+        .unwrap_or_default();
+    // Typst などの依存更新も「ツールチェーンの変更」として扱う
+    let lockfile = fs::read("Cargo.lock").unwrap_or_default();
+
+    let mut hasher = Sha256::new();
+    hasher.update(CACHE_VERSION.to_le_bytes());
+    hasher.update(rustc_version.as_bytes());
+    hasher.update(hash_bytes(&lockfile).as_bytes());
+    hasher.update(hash_bytes(preamble.as_bytes()).as_bytes());
+    // release ビルドだけ HTML を minify するので分けておく
+    hasher.update([u8::from(cfg!(debug_assertions))]);
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
@@ -0,0 +1,251 @@
+use anyhow::{bail, Result};
+use regex::{Captures, Regex};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use crate::{
+    cache,
+    diagnostics::{self, Diagnostic, Level},
+    frontmatter::{is_valid_slug, PRIVATE_DIR},
+};
+
+/// スラッグの決め方を切り替える環境変数（`name` / `path`）
+pub const SLUG_STYLE_ENV_KEY: &str = "RODIN_SLUG_STYLE";
+/// バンドルのアセットのコピー先（/blog/{slug}/{path} で配信）
+const BUNDLE_OUT_DIR: &str = "static/generated/posts";
+/// 下書きのバンドルのアセットのコピー先（`PRIVATE_DIR` の下。/preview/{slug}/{path} で配信）
+const DRAFT_BUNDLE_OUT_DIR: &str = "posts";
+const BUNDLE_INDEX: &str = "index.typ";
+
+static ASSET_ATTR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r##"\b(src|href)="([^"#?:]+)""##).expect("valid regex"));
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SlugStyle {
+    /// ファイル名（バンドルはディレクトリ名）だけ: content/2025/foo.typ -> foo
+    #[default]
+    Name,
+    /// content からのパスを `-` でつなぐ: content/2025/foo.typ -> 2025-foo
+    Path,
+}
+
+impl SlugStyle {
+    pub fn from_env() -> Self {
+        match std::env::var(SLUG_STYLE_ENV_KEY).as_deref() {
+            Ok("path") => SlugStyle::Path,
+            Ok("name") | Err(_) => SlugStyle::Name,
+            Ok(other) => {
+                println!("cargo:warning=unknown {SLUG_STYLE_ENV_KEY} `{other}`; using `name`");
+                SlugStyle::Name
+            }
+        }
+    }
+}
+
+/// content 以下で見つけた記事 1 本
+pub struct PostSource {
+    pub slug: String,
+    /// content/2025/foo.typ や content/foo/index.typ
+    pub path: PathBuf,
+    /// `content/<slug>/index.typ` 形式ならそのディレクトリ
+    pub bundle_dir: Option<PathBuf>,
+}
+
+impl PostSource {
+    pub fn display_path(&self) -> String {
+        self.path.to_string_lossy().replace('\\', "/")
+    }
+}
+
+/// content 以下を再帰的に探す。`_` / `.` で始まるものは記事として扱わない
+pub fn collect_sources(root: &Path, style: SlugStyle) -> Result<Vec<PostSource>> {
+    let out = scan_sources(root, style)?;
+    // /blog/{slug} で返せないスラッグは警告に留める（check の invalid-slug でも拾う。new では作らせない）
+    for post in &out {
+        if !is_valid_slug(&post.slug) {
+            diagnostics::emit(&Diagnostic::plain(
+                Level::Warning,
+                &post.display_path(),
+                format!(
+                    "slug `{}` cannot be served as /blog/{} (use letters, digits, `-` and `_`)",
+                    post.slug, post.slug
+                ),
+            ));
+        }
+    }
+
+    let mut seen: HashMap<&str, &Path> = HashMap::new();
+    for post in &out {
+        if let Some(prev) = seen.insert(&post.slug, &post.path) {
+            bail!(
+                "duplicate slug `{}`: {} and {} (set {SLUG_STYLE_ENV_KEY}=path or rename one)",
+                post.slug,
+                prev.display(),
+                post.path.display()
+            );
+        }
+    }
+    Ok(out)
+}
+
+/// `collect_sources` からスラッグの検査を抜いたもの（`rodin-content check` はまとめて報告する）
+pub fn scan_sources(root: &Path, style: SlugStyle) -> Result<Vec<PostSource>> {
+    let mut out = Vec::new();
+    walk(root, &mut Vec::new(), style, &mut out)?;
+    Ok(out)
+}
+
+fn walk(
+    dir: &Path,
+    prefix: &mut Vec<String>,
+    style: SlugStyle,
+    out: &mut Vec<PostSource>,
+) -> Result<()> {
+    let mut entries: Vec<_> = fs::read_dir(dir)?.filter_map(|e| e.ok()).collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('_') || name.starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            prefix.push(name);
+            let index = path.join(BUNDLE_INDEX);
+            if index.is_file() {
+                out.push(PostSource {
+                    slug: derive_slug(prefix, style),
+                    path: index,
+                    bundle_dir: Some(path),
+                });
+            } else {
+                walk(&path, prefix, style, out)?;
+            }
+            prefix.pop();
+        } else if path.extension().and_then(|s| s.to_str()) == Some("typ") {
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            prefix.push(stem.to_string());
+            out.push(PostSource {
+                slug: derive_slug(prefix, style),
+                path,
+                bundle_dir: None,
+            });
+            prefix.pop();
+        }
+    }
+    Ok(())
+}
+
+fn derive_slug(components: &[String], style: SlugStyle) -> String {
+    match style {
+        SlugStyle::Name => components.last().cloned().unwrap_or_default(),
+        SlugStyle::Path => components.join("-"),
+    }
+}
+
+/// バンドル内のファイル（index.typ 以外）を相対パスで列挙する
+pub fn bundle_files(bundle_dir: &Path) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut stack = vec![bundle_dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                stack.push(path);
+                continue;
+            }
+            let Ok(rel) = path.strip_prefix(bundle_dir) else {
+                continue;
+            };
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            if rel != BUNDLE_INDEX {
+                files.push(rel);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// キャッシュの判定用にバンドル内の全ファイル（サブの .typ も含む）をハッシュする
+pub fn hash_bundle(bundle_dir: &Path, files: &[String]) -> Result<BTreeMap<String, String>> {
+    files
+        .iter()
+        .map(|rel| {
+            let bytes = fs::read(bundle_dir.join(rel))?;
+            Ok((format!("bundle:{rel}"), cache::hash_bytes(&bytes)))
+        })
+        .collect()
+}
+
+/// 公開用のアセット（.typ 以外）を static/generated/posts/<slug>/ にコピーする
+///
+/// 下書きは static の外（generated-private/posts/<slug>/）に置き、プレビューからだけ読む。
+pub fn copy_bundle_assets(
+    slug: &str,
+    bundle_dir: &Path,
+    assets: &[String],
+    draft: bool,
+) -> Result<()> {
+    let out_dir = bundle_out_dir(draft).join(slug);
+    if out_dir.exists() {
+        fs::remove_dir_all(&out_dir)?;
+    }
+    for rel in assets {
+        let dest = out_dir.join(rel);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(bundle_dir.join(rel), dest)?;
+    }
+    Ok(())
+}
+
+/// 消えた記事（`draft` なら下書きでなくなった記事も）のバンドルアセットを消す
+pub fn prune_bundle_assets(keep: &HashSet<&str>, draft: bool) -> Result<()> {
+    let dir = bundle_out_dir(draft);
+    if !dir.exists() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        if !keep.contains(entry.file_name().to_string_lossy().as_ref()) {
+            fs::remove_dir_all(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+fn bundle_out_dir(draft: bool) -> PathBuf {
+    if draft {
+        Path::new(PRIVATE_DIR).join(DRAFT_BUNDLE_OUT_DIR)
+    } else {
+        PathBuf::from(BUNDLE_OUT_DIR)
+    }
+}
+
+/// HTML 中の相対パスのうちバンドルのアセットを指すものを `{base}/...` に書き換える
+///
+/// `base` は公開記事なら `/blog/{slug}`、下書きなら `/preview/{slug}`。
+pub fn rewrite_asset_links(html: &str, base: &str, assets: &[String]) -> String {
+    if assets.is_empty() {
+        return html.to_string();
+    }
+    let assets: HashSet<&str> = assets.iter().map(String::as_str).collect();
+    ASSET_ATTR_RE
+        .replace_all(html, |caps: &Captures| {
+            let value = caps[2].trim_start_matches("./");
+            if assets.contains(value) {
+                format!("{}=\"{base}/{value}\"", &caps[1])
+            } else {
+                caps[0].to_string()
+            }
+        })
+        .into_owned()
+}
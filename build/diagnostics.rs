@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    sync::{Mutex, OnceLock},
+};
+use typst_library::{
+    diag::{Severity, SourceDiagnostic},
+    syntax::Source,
+};
+
+static FORMAT: OnceLock<DiagnosticFormat> = OnceLock::new();
+static EMITTED: Mutex<Vec<Diagnostic>> = Mutex::new(Vec::new());
+
+/// 診断の出力先と形式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiagnosticFormat {
+    /// build.rs 用（各行を `cargo:warning=` で出す）
+    #[default]
+    Cargo,
+    /// 人が読む形式で stderr へ
+    Human,
+    /// 1 行 1 件の JSON で stderr へ
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Error => f.write_str("error"),
+            Level::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+/// 元の .typ ファイルの位置に戻した診断
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub level: Level,
+    /// `rodin-content check` のルール名（`missing-title` など）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    pub file: String,
+    /// 1 始まり
+    pub line: Option<usize>,
+    /// 1 始まり（文字単位）
+    pub column: Option<usize>,
+    pub end_column: Option<usize>,
+    pub message: String,
+    pub hints: Vec<String>,
+    /// 該当行のソース
+    pub excerpt: Option<String>,
+}
+
+/// コンパイルに渡した文字列の構成（プリアンブル + 前置き + 本文）
+pub struct SourceLayout<'a> {
+    pub file: &'a str,
+    pub preamble_file: &'a str,
+    /// プリアンブルが占める行数
+    pub preamble_lines: usize,
+    /// 本文が始まる行（0 始まり）。プリアンブルとの間は Rust 側で差し込んだ行
+    pub body_start: usize,
+}
+
+impl Diagnostic {
+    pub fn plain(level: Level, file: &str, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            code: None,
+            file: file.to_string(),
+            line: None,
+            column: None,
+            end_column: None,
+            message: message.into(),
+            hints: Vec::new(),
+            excerpt: None,
+        }
+    }
+
+    pub fn from_typst(diag: &SourceDiagnostic, main: &Source, layout: &SourceLayout) -> Self {
+        let level = match diag.severity {
+            Severity::Error => Level::Error,
+            Severity::Warning => Level::Warning,
+        };
+        let mut out = Self::plain(level, layout.file, diag.message.to_string());
+        out.hints = diag.hints.iter().map(|h| h.to_string()).collect();
+
+        // 他ファイル（パッケージなど）や位置なしのスパンはファイル名だけ出す
+        let range = (diag.span.id() == Some(main.id()))
+            .then(|| main.range(diag.span))
+            .flatten();
+        let Some(range) = range else {
+            return out;
+        };
+
+        let text = main.text();
+        let start = range.start.min(text.len());
+        let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+        let line = text[..start].matches('\n').count();
+        let column = text[line_start..start].chars().count() + 1;
+        let width = text[start..range.end.clamp(start, line_end)]
+            .chars()
+            .count()
+            .max(1);
+
+        if line < layout.preamble_lines {
+            out.file = layout.preamble_file.to_string();
+            out.line = Some(line + 1);
+        } else if line >= layout.body_start {
+            out.line = Some(line - layout.body_start + 1);
+        } else {
+            // Rust 側で差し込んだ行なので位置は出さない
+            return out;
+        }
+        out.column = Some(column);
+        out.end_column = Some(column + width);
+        out.excerpt = Some(text[line_start..line_end].to_string());
+        out
+    }
+
+    /// rustc 風の表示
+    pub fn render(&self) -> String {
+        let mut out = match &self.code {
+            Some(code) => format!("{}[{code}]: {}\n", self.level, self.message),
+            None => format!("{}: {}\n", self.level, self.message),
+        };
+        let location = match (self.line, self.column) {
+            (Some(line), Some(col)) => format!("{}:{line}:{col}", self.file),
+            (Some(line), None) => format!("{}:{line}", self.file),
+            _ => self.file.clone(),
+        };
+        let gutter = self.line.map_or(1, |l| l.to_string().len());
+        let pad = " ".repeat(gutter);
+        if !self.file.is_empty() {
+            out.push_str(&format!("{pad}--> {location}\n"));
+        }
+        if let (Some(line), Some(col), Some(excerpt)) = (self.line, self.column, &self.excerpt) {
+            let width = self
+                .end_column
+                .unwrap_or(col + 1)
+                .saturating_sub(col)
+                .max(1);
+            let excerpt = excerpt.replace('\t', " ");
+            out.push_str(&format!("{pad} |\n"));
+            out.push_str(&format!("{line} | {excerpt}\n"));
+            out.push_str(&format!(
+                "{pad} | {}{}\n",
+                " ".repeat(col - 1),
+                "^".repeat(width)
+            ));
+        }
+        for hint in &self.hints {
+            out.push_str(&format!("{pad} = hint: {hint}\n"));
+        }
+        out.trim_end().to_string()
+    }
+}
+
+/// 公開記事のコンパイル失敗。Display は人が読む形式
+#[derive(Debug)]
+pub struct CompileError(pub Vec<Diagnostic>);
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rendered: Vec<String> = self.0.iter().map(Diagnostic::render).collect();
+        f.write_str(&rendered.join("\n\n"))
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+/// 最初に一度だけ呼ぶ（呼ばなければ Cargo 形式）
+pub fn set_format(format: DiagnosticFormat) {
+    let _ = FORMAT.set(format);
+}
+
+pub fn emit(diag: &Diagnostic) {
+    match FORMAT.get().copied().unwrap_or_default() {
+        DiagnosticFormat::Cargo => {
+            for line in diag.render().lines() {
+                println!("cargo:warning={line}");
+            }
+        }
+        DiagnosticFormat::Human => eprintln!("{}\n", diag.render()),
+        DiagnosticFormat::Json => match serde_json::to_string(diag) {
+            Ok(json) => eprintln!("{json}"),
+            Err(e) => eprintln!("failed to serialize diagnostic: {e}"),
+        },
+    }
+    if let Ok(mut emitted) = EMITTED.lock() {
+        emitted.push(diag.clone());
+    }
+}
+
+/// ビルド中のエラーを出す（CompileError なら中の診断を 1 件ずつ）
+pub fn emit_error(err: &anyhow::Error) {
+    match err.downcast_ref::<CompileError>() {
+        Some(CompileError(diags)) => diags.iter().for_each(emit),
+        None => emit(&Diagnostic::plain(Level::Error, "", err.to_string())),
+    }
+}
+
+/// 本体のビルドは続けられる失敗（OG 画像や PDF など）を警告として出す
+pub fn emit_as_warnings(err: &anyhow::Error, file: &str, what: &str) {
+    let diags = match err.downcast_ref::<CompileError>() {
+        Some(CompileError(diags)) => diags.clone(),
+        None => vec![Diagnostic::plain(Level::Error, file, err.to_string())],
+    };
+    for mut diag in diags {
+        diag.level = Level::Warning;
+        diag.message = format!("{what}: {}", diag.message);
+        emit(&diag);
+    }
+}
+
+/// これまでに出した警告の数（--deny-warnings 用）
+pub fn warning_count() -> usize {
+    EMITTED
+        .lock()
+        .map(|emitted| emitted.iter().filter(|d| d.level == Level::Warning).count())
+        .unwrap_or(0)
+}
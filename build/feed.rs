@@ -0,0 +1,420 @@
+use crate::{
+    config::Site,
+    frontmatter::{tag_key, tag_path, FrontMatter},
+};
+use anyhow::Result;
+use regex::{Captures, Regex};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+const FEED_LIMIT: usize = 50;
+
+static URL_ATTR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\b(href|src|srcset|poster)="([^"]*)""#).expect("valid regex"));
+
+/// `feed.atom` / `feed.xml` / `feed.json` と、タグごとのフィードを書き出す（戻り値は記事数とタグ数）
+pub fn write_feeds(
+    metas: &[FrontMatter],
+    site: &Site,
+    generated_dir: &str,
+) -> Result<(usize, usize)> {
+    let out_dir = PathBuf::from(generated_dir);
+    fs::create_dir_all(&out_dir)?;
+
+    let site_url = site.url.as_str();
+    let entries = load_entries(metas, site)?;
+    let info = FeedInfo {
+        title: site.title.clone(),
+        description: format!("{}のブログ", site.author),
+        home_url: format!("{site_url}/"),
+        feed_path: String::new(),
+        site_url: site_url.to_string(),
+        author: site.author.clone(),
+        lang: site.lang().to_string(),
+    };
+    // 全体のフィードは新しい順に FEED_LIMIT 件まで
+    let latest: Vec<&FeedEntry> = entries.iter().take(FEED_LIMIT).collect();
+    write_feed_set(&out_dir, "feed", &info, &latest)?;
+
+    // タグ別: static/generated/feeds/tags/{encoded}.{atom,xml,json}
+    let tags_dir = out_dir.join("feeds/tags");
+    if tags_dir.exists() {
+        fs::remove_dir_all(&tags_dir)?;
+    }
+    fs::create_dir_all(&tags_dir)?;
+    // 全記事からタグごとにまとめてから、タグごとに FEED_LIMIT 件で切る
+    let mut by_tag: BTreeMap<String, (String, Vec<&FeedEntry>)> = BTreeMap::new();
+    for entry in &entries {
+        for tag in &entry.tags {
+            if tag_key(tag).is_empty() {
+                continue;
+            }
+            let (_, tag_entries) = by_tag
+                .entry(tag_path(tag))
+                .or_insert_with(|| (tag.trim().to_string(), Vec::new()));
+            // 同じ記事に表記違いのタグが 2 つあっても 1 回だけ
+            if tag_entries.len() < FEED_LIMIT
+                && tag_entries
+                    .last()
+                    .is_none_or(|last| !std::ptr::eq(*last, entry))
+            {
+                tag_entries.push(entry);
+            }
+        }
+    }
+    for (path, (name, tag_entries)) in &by_tag {
+        let info = FeedInfo {
+            title: format!("#{name} - {}", site.title),
+            description: format!("「{name}」タグが付いた記事"),
+            home_url: format!("{site_url}{path}"),
+            feed_path: path.clone(),
+            site_url: site_url.to_string(),
+            author: site.author.clone(),
+            lang: site.lang().to_string(),
+        };
+        let stem = path.trim_start_matches("/tags/");
+        write_feed_set(&tags_dir, stem, &info, tag_entries)?;
+    }
+
+    Ok((latest.len(), by_tag.len()))
+}
+
+struct FeedInfo {
+    title: String,
+    description: String,
+    home_url: String,
+    /// `/feed.atom` の前に付けるパス（サイト全体なら空）
+    feed_path: String,
+    site_url: String,
+    author: String,
+    lang: String,
+}
+
+impl FeedInfo {
+    fn feed_url(&self, ext: &str) -> String {
+        format!("{}{}/feed.{ext}", self.site_url, self.feed_path)
+    }
+}
+
+struct FeedEntry {
+    url: String,
+    title: String,
+    summary: Option<String>,
+    content_html: String,
+    author: String,
+    published: Option<String>,
+    updated: Option<String>,
+    tags: Vec<String>,
+}
+
+/// 一覧に載せる全記事を新しい順に（件数はフィードごとに切る）
+fn load_entries(metas: &[FrontMatter], site: &Site) -> Result<Vec<FeedEntry>> {
+    let mut sorted: Vec<&FrontMatter> = metas.iter().collect();
+    sorted.sort_by(|a, b| {
+        let a_time = a.published_at.as_deref().unwrap_or("");
+        let b_time = b.published_at.as_deref().unwrap_or("");
+        b_time.cmp(a_time)
+    });
+
+    let mut entries = Vec::with_capacity(sorted.len());
+    for meta in sorted {
+        let html_path = Path::new("static").join(&meta.html);
+        let content_html = absolutize_urls(&fs::read_to_string(&html_path)?, &site.url);
+        entries.push(FeedEntry {
+            url: format!("{}/blog/{}", site.url, meta.slug),
+            title: meta.title.clone().unwrap_or_else(|| meta.slug.clone()),
+            summary: meta
+                .meta
+                .get("description")
+                .or_else(|| meta.meta.get("og:description"))
+                .cloned(),
+            content_html,
+            author: meta
+                .meta
+                .get("author")
+                .cloned()
+                .unwrap_or_else(|| site.author.clone()),
+            published: meta.published_at.as_deref().map(normalize_iso8601),
+            updated: meta
+                .updated_at
+                .as_deref()
+                .or(meta.published_at.as_deref())
+                .map(normalize_iso8601),
+            tags: meta.tags.clone(),
+        });
+    }
+    Ok(entries)
+}
+
+/// `/blog/...` や `/images/...` をサイトの URL から始まる形にする
+/// （xml:base があるのは Atom だけで、RSS と JSON Feed では読者側が解決できない）
+fn absolutize_urls(html: &str, site_url: &str) -> String {
+    let absolute = |url: &str| {
+        if url.starts_with('/') && !url.starts_with("//") {
+            format!("{site_url}{url}")
+        } else {
+            url.to_string()
+        }
+    };
+    URL_ATTR_RE
+        .replace_all(html, |caps: &Captures| {
+            let value = if &caps[1] == "srcset" {
+                caps[2]
+                    .split(',')
+                    .map(|candidate| absolute(candidate.trim_start()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            } else {
+                absolute(&caps[2])
+            };
+            format!("{}=\"{value}\"", &caps[1])
+        })
+        .into_owned()
+}
+
+fn write_feed_set(dir: &Path, stem: &str, info: &FeedInfo, entries: &[&FeedEntry]) -> Result<()> {
+    fs::write(dir.join(format!("{stem}.atom")), render_atom(info, entries))?;
+    fs::write(dir.join(format!("{stem}.xml")), render_rss(info, entries))?;
+    fs::write(
+        dir.join(format!("{stem}.json")),
+        serde_json::to_string_pretty(&render_json_feed(info, entries))?,
+    )?;
+    Ok(())
+}
+
+fn render_atom(info: &FeedInfo, entries: &[&FeedEntry]) -> String {
+    let updated = entries
+        .iter()
+        .filter_map(|e| e.updated.as_deref())
+        .max()
+        .map(str::to_string)
+        .unwrap_or_else(|| "1970-01-01T00:00:00.000Z".to_string());
+
+    let mut body = String::new();
+    for entry in entries {
+        body.push_str("  <entry>\n");
+        body.push_str(&format!(
+            "    <title>{}</title>\n",
+            escape_xml(&entry.title)
+        ));
+        body.push_str(&format!(
+            "    <link rel=\"alternate\" type=\"text/html\" href=\"{}\" />\n",
+            escape_xml(&entry.url)
+        ));
+        body.push_str(&format!("    <id>{}</id>\n", escape_xml(&entry.url)));
+        if let Some(published) = entry.published.as_ref() {
+            body.push_str(&format!("    <published>{published}</published>\n"));
+        }
+        body.push_str(&format!(
+            "    <updated>{}</updated>\n",
+            entry.updated.as_deref().unwrap_or(&updated)
+        ));
+        body.push_str(&format!(
+            "    <author><name>{}</name></author>\n",
+            escape_xml(&entry.author)
+        ));
+        for tag in &entry.tags {
+            body.push_str(&format!("    <category term=\"{}\" />\n", escape_xml(tag)));
+        }
+        if let Some(summary) = entry.summary.as_ref() {
+            body.push_str(&format!("    <summary>{}</summary>\n", escape_xml(summary)));
+        }
+        body.push_str(&format!(
+            "    <content type=\"html\">{}</content>\n",
+            escape_xml(&entry.content_html)
+        ));
+        body.push_str("  </entry>\n");
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xml:lang="{lang}" xml:base="{base}/">
+  <title>{title}</title>
+  <subtitle>{subtitle}</subtitle>
+  <link rel="alternate" type="text/html" href="{home}" />
+  <link rel="self" type="application/atom+xml" href="{self_url}" />
+  <id>{self_url}</id>
+  <updated>{updated}</updated>
+  <author><name>{author}</name></author>
+{body}</feed>
+"#,
+        base = escape_xml(&info.site_url),
+        title = escape_xml(&info.title),
+        subtitle = escape_xml(&info.description),
+        home = escape_xml(&info.home_url),
+        self_url = escape_xml(&info.feed_url("atom")),
+        author = escape_xml(&info.author),
+        lang = escape_xml(&info.lang),
+    )
+}
+
+fn render_rss(info: &FeedInfo, entries: &[&FeedEntry]) -> String {
+    let last_build = entries
+        .iter()
+        .filter_map(|e| e.updated.as_deref())
+        .max()
+        .and_then(rfc822_date);
+
+    let mut body = String::new();
+    for entry in entries {
+        body.push_str("    <item>\n");
+        body.push_str(&format!(
+            "      <title>{}</title>\n",
+            escape_xml(&entry.title)
+        ));
+        body.push_str(&format!("      <link>{}</link>\n", escape_xml(&entry.url)));
+        body.push_str(&format!(
+            "      <guid isPermaLink=\"true\">{}</guid>\n",
+            escape_xml(&entry.url)
+        ));
+        if let Some(date) = entry.published.as_deref().and_then(rfc822_date) {
+            body.push_str(&format!("      <pubDate>{date}</pubDate>\n"));
+        }
+        body.push_str(&format!(
+            "      <dc:creator>{}</dc:creator>\n",
+            escape_xml(&entry.author)
+        ));
+        for tag in &entry.tags {
+            body.push_str(&format!("      <category>{}</category>\n", escape_xml(tag)));
+        }
+        if let Some(summary) = entry.summary.as_ref() {
+            body.push_str(&format!(
+                "      <description>{}</description>\n",
+                escape_xml(summary)
+            ));
+        }
+        body.push_str(&format!(
+            "      <content:encoded>{}</content:encoded>\n",
+            escape_xml(&entry.content_html)
+        ));
+        body.push_str("    </item>\n");
+    }
+
+    let last_build_html = last_build
+        .map(|d| format!("    <lastBuildDate>{d}</lastBuildDate>\n"))
+        .unwrap_or_default();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:content="http://purl.org/rss/1.0/modules/content/" xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <channel>
+    <title>{title}</title>
+    <link>{home}</link>
+    <description>{description}</description>
+    <language>{lang}</language>
+    <atom:link rel="self" type="application/rss+xml" href="{self_url}" />
+{last_build_html}{body}  </channel>
+</rss>
+"#,
+        title = escape_xml(&info.title),
+        home = escape_xml(&info.home_url),
+        description = escape_xml(&info.description),
+        self_url = escape_xml(&info.feed_url("xml")),
+        lang = escape_xml(&info.lang),
+    )
+}
+
+fn render_json_feed(info: &FeedInfo, entries: &[&FeedEntry]) -> Value {
+    let items: Vec<Value> = entries
+        .iter()
+        .map(|entry| {
+            let mut item = json!({
+                "id": entry.url,
+                "url": entry.url,
+                "title": entry.title,
+                "content_html": entry.content_html,
+                "authors": [{ "name": entry.author }],
+            });
+            if let Some(summary) = entry.summary.as_ref() {
+                item["summary"] = json!(summary);
+            }
+            if let Some(published) = entry.published.as_ref() {
+                item["date_published"] = json!(published);
+            }
+            if let Some(updated) = entry.updated.as_ref() {
+                item["date_modified"] = json!(updated);
+            }
+            if !entry.tags.is_empty() {
+                item["tags"] = json!(entry.tags);
+            }
+            item
+        })
+        .collect();
+
+    json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": info.title,
+        "description": info.description,
+        "home_page_url": info.home_url,
+        "feed_url": info.feed_url("json"),
+        "language": info.lang,
+        "authors": [{ "name": info.author, "url": format!("{}/", info.site_url) }],
+        "items": items,
+    })
+}
+
+fn normalize_iso8601(date: &str) -> String {
+    let date = date.trim();
+    if date.contains('T') {
+        date.to_string()
+    } else {
+        format!("{date}T00:00:00.000Z")
+    }
+}
+
+/// `2025-01-02T03:04:05+09:00` 形式を RSS 用の RFC 822 形式に変換する
+fn rfc822_date(iso: &str) -> Option<String> {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let (date, time) = iso.split_once('T').unwrap_or((iso, "00:00:00Z"));
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let hms = time.get(..8).filter(|t| t.len() == 8).unwrap_or("00:00:00");
+    let offset = match time.find(['+', '-']) {
+        Some(pos) => time[pos..].replace(':', ""),
+        None => "+0000".to_string(),
+    };
+
+    // 1970-01-01 (木曜) からの日数で曜日を求める
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    let weekday = WEEKDAYS[days.rem_euclid(7) as usize];
+
+    Some(format!(
+        "{weekday}, {day:02} {} {year} {hms} {offset}",
+        MONTHS[(month - 1) as usize]
+    ))
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
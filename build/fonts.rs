@@ -19,17 +19,21 @@ const BOLD_FONT_WOFF2_OUT: &str = "static/build/IBMPlexSansJP-Bold.subset.woff2"
 
 const TEXT_SOURCES: &[&str] = &[
     "src/app/handlers.rs",      // not_found_response HTML
+    "src/app/search/query.rs",  // search syntax errors
     "src/components.rs",        // top/profile/blog chrome
     "src/components/search.rs", // search page strings
+    "src/components/tags.rs",   // tag list strings
     "static/app.js",            // UI strings in client JS
     "static/preamble.typ",      // preamble for typst
+    "rodin.toml",               // site name, tagline, breadcrumb labels
 ];
 
 /// Additional characters to include in Bold font subset (beyond H1 headings)
 /// These are characters used with font-weight: 700 in critical UI elements
-const BOLD_EXTRA_CHARS: &str = "すずねーう目次プロフィール検索結果件見つかりました";
+const BOLD_EXTRA_CHARS: &str = "目次プロフィール検索結果件見つかりました";
 
-pub fn subset_regular_font() -> Result<()> {
+/// `bold_extra` is shown in bold as well (the site name in the header).
+pub fn subset_regular_font(bold_extra: &str) -> Result<()> {
     println!("cargo:rerun-if-changed={REGULAR_FONT_SRC}");
     println!("cargo:rerun-if-changed={BOLD_FONT_SRC}");
     println!("cargo:rerun-if-changed={SEMIBOLD_FONT_SRC}");
@@ -45,7 +49,7 @@ pub fn subset_regular_font() -> Result<()> {
     }
 
     // Collect minimal glyphs for Bold font (H1 headings + extra chars)
-    let bold_glyphs = collect_bold_glyphs()?;
+    let bold_glyphs = collect_bold_glyphs(bold_extra)?;
 
     subset_font(
         SEMIBOLD_FONT_SRC,
@@ -90,14 +94,13 @@ fn collect_glyphs() -> Result<BTreeSet<char>> {
 
 /// Collect minimal glyphs for Bold font:
 /// - Characters from `= ` lines (H1 headings) in .typ files
-/// - Extra characters defined in BOLD_EXTRA_CHARS
-fn collect_bold_glyphs() -> Result<BTreeSet<char>> {
+/// - Extra characters defined in BOLD_EXTRA_CHARS and `extra`
+fn collect_bold_glyphs(extra: &str) -> Result<BTreeSet<char>> {
     let mut set = BTreeSet::new();
 
     // Add extra characters for Bold font
-    for ch in BOLD_EXTRA_CHARS.chars() {
-        set.insert(ch);
-    }
+    set.extend(BOLD_EXTRA_CHARS.chars());
+    set.extend(extra.chars());
 
     // Collect H1 headings from .typ files
     collect_h1_headings_from_dir("content", &mut set)?;
@@ -114,7 +117,11 @@ fn collect_h1_headings_from_dir(dir: &str, set: &mut BTreeSet<char>) -> Result<(
     for entry in fs::read_dir(dir).with_context(|| format!("reading directory {dir}"))? {
         let entry = entry?;
         let path = entry.path();
-        if entry.file_type()?.is_file() && path.extension().is_some_and(|ext| ext == "typ") {
+        let file_type = entry.file_type()?;
+        // 入れ子のディレクトリやバンドル（content/<slug>/index.typ）も拾う
+        if file_type.is_dir() {
+            collect_h1_headings_from_dir(&path.to_string_lossy(), set)?;
+        } else if file_type.is_file() && path.extension().is_some_and(|ext| ext == "typ") {
             collect_h1_headings_from_file(&path, set)?;
         }
     }
@@ -153,7 +160,11 @@ fn collect_from_content_dir(dir: &str, set: &mut BTreeSet<char>) -> Result<()> {
     for entry in fs::read_dir(dir).with_context(|| format!("reading directory {dir}"))? {
         let entry = entry?;
         let path = entry.path();
-        if entry.file_type()?.is_file() && path.extension().is_some_and(|ext| ext == "typ") {
+        let file_type = entry.file_type()?;
+        // 入れ子のディレクトリやバンドル（content/<slug>/index.typ）も拾う
+        if file_type.is_dir() {
+            collect_from_content_dir(&path.to_string_lossy(), set)?;
+        } else if file_type.is_file() && path.extension().is_some_and(|ext| ext == "typ") {
             collect_from_file(&path, set)?;
         }
     }
@@ -0,0 +1,752 @@
+//! Typst が出す HTML を GitHub Flavored Markdown に変換する
+//!
+//! 入力は Typst（と minify-html）の出力だけを想定した小さなパーサーで読む。
+//! 閉じタグの省略などブラウザ並みの補正はしない。
+
+/// 中身を持たない要素
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+/// 中身をそのまま読み飛ばす要素
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+/// 段落を区切る要素（これ以外はインラインとして扱う）
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "ul",
+];
+
+enum Node {
+    Element {
+        name: String,
+        attrs: Attrs,
+        children: Vec<Node>,
+    },
+    Text(String),
+}
+
+impl Node {
+    fn name(&self) -> &str {
+        match self {
+            Node::Element { name, .. } => name,
+            Node::Text(_) => "",
+        }
+    }
+
+    fn attr(&self, key: &str) -> Option<&str> {
+        match self {
+            Node::Element { attrs, .. } => attrs
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str()),
+            Node::Text(_) => None,
+        }
+    }
+
+    fn has_class(&self, class: &str) -> bool {
+        self.attr("class")
+            .is_some_and(|c| c.split_whitespace().any(|c| c == class))
+    }
+
+    fn children(&self) -> &[Node] {
+        match self {
+            Node::Element { children, .. } => children,
+            Node::Text(_) => &[],
+        }
+    }
+
+    fn is_block(&self) -> bool {
+        BLOCK_ELEMENTS.contains(&self.name())
+    }
+
+    fn text_content(&self) -> String {
+        match self {
+            Node::Text(t) => t.clone(),
+            Node::Element { children, .. } => children.iter().map(Node::text_content).collect(),
+        }
+    }
+}
+
+/// 本文の HTML を GFM にする（脚注は末尾にまとめる）
+pub fn html_to_gfm(html: &str) -> String {
+    let nodes = parse(html);
+    let mut conv = Converter::default();
+    let mut out = conv.blocks(&nodes).join("\n\n");
+    if !conv.footnotes.is_empty() {
+        let notes: Vec<String> = conv
+            .footnotes
+            .iter()
+            .map(|(label, body)| format!("[^{label}]: {}", indent_continuation(body, "    ")))
+            .collect();
+        out.push_str("\n\n");
+        out.push_str(&notes.join("\n"));
+    }
+    out
+}
+
+fn parse(html: &str) -> Vec<Node> {
+    // (要素名, 属性, 子) のスタック。先頭は文書全体
+    let mut stack: Vec<OpenElement> = vec![(String::new(), Vec::new(), Vec::new())];
+    let mut rest = html;
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            push_text(&mut stack, rest);
+            break;
+        };
+        push_text(&mut stack, &rest[..lt]);
+        rest = &rest[lt..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |i| &comment[i + 3..]);
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |i| &rest[i + 1..]);
+            continue;
+        }
+        if let Some(close) = rest.strip_prefix("</") {
+            let end = close.find('>').unwrap_or(close.len());
+            let name = close[..end].trim().to_ascii_lowercase();
+            rest = close.get(end + 1..).unwrap_or("");
+            // 対応する開きタグまで閉じる（無ければ無視）
+            if let Some(pos) = stack.iter().rposition(|(n, _, _)| *n == name) {
+                while stack.len() > pos {
+                    close_top(&mut stack);
+                }
+            }
+            continue;
+        }
+
+        let Some((name, attrs, self_closing, consumed)) = parse_tag(rest) else {
+            // タグではない `<`
+            push_text(&mut stack, "<");
+            rest = &rest[1..];
+            continue;
+        };
+        rest = &rest[consumed..];
+        if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+            let end_tag = format!("</{name}");
+            let end = rest
+                .to_ascii_lowercase()
+                .find(&end_tag)
+                .unwrap_or(rest.len());
+            rest = rest[end..].find('>').map_or("", |i| &rest[end + i + 1..]);
+            continue;
+        }
+        if self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+            stack.last_mut().expect("root").2.push(Node::Element {
+                name,
+                attrs,
+                children: Vec::new(),
+            });
+        } else {
+            stack.push((name, attrs, Vec::new()));
+        }
+    }
+    while stack.len() > 1 {
+        close_top(&mut stack);
+    }
+    stack.pop().map(|(_, _, nodes)| nodes).unwrap_or_default()
+}
+
+type Attrs = Vec<(String, String)>;
+type OpenElement = (String, Attrs, Vec<Node>);
+
+fn push_text(stack: &mut [OpenElement], raw: &str) {
+    if !raw.is_empty() {
+        if let Some(top) = stack.last_mut() {
+            top.2.push(Node::Text(decode_entities(raw)));
+        }
+    }
+}
+
+fn close_top(stack: &mut Vec<OpenElement>) {
+    if let Some((name, attrs, children)) = stack.pop() {
+        if let Some(parent) = stack.last_mut() {
+            parent.2.push(Node::Element {
+                name,
+                attrs,
+                children,
+            });
+        }
+    }
+}
+
+/// `<name attr="v" ...>` を読む。戻り値の usize は消費したバイト数
+fn parse_tag(s: &str) -> Option<(String, Attrs, bool, usize)> {
+    let bytes = s.as_bytes();
+    let mut i = 1;
+    let name_start = i;
+    while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'-') {
+        i += 1;
+    }
+    if i == name_start {
+        return None;
+    }
+    let name = s[name_start..i].to_ascii_lowercase();
+    let mut attrs = Vec::new();
+    loop {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        match bytes.get(i) {
+            None => return Some((name, attrs, false, s.len())),
+            Some(b'>') => return Some((name, attrs, false, i + 1)),
+            Some(b'/') if bytes.get(i + 1) == Some(&b'>') => {
+                return Some((name, attrs, true, i + 2));
+            }
+            Some(b'/') => {
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        let key_start = i;
+        while i < bytes.len() && !matches!(bytes[i], b'=' | b'>' | b'/') {
+            if bytes[i].is_ascii_whitespace() {
+                break;
+            }
+            i += 1;
+        }
+        let key = s[key_start..i].to_ascii_lowercase();
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let mut value = String::new();
+        if bytes.get(i) == Some(&b'=') {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            match bytes.get(i) {
+                Some(&q @ (b'"' | b'\'')) => {
+                    let start = i + 1;
+                    let end = s[start..].find(q as char).map_or(s.len(), |e| start + e);
+                    value = decode_entities(&s[start..end]);
+                    i = (end + 1).min(s.len());
+                }
+                _ => {
+                    // minify-html はクォートを外すことがある
+                    let start = i;
+                    while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+                        i += 1;
+                    }
+                    value = decode_entities(&s[start..i]);
+                }
+            }
+        }
+        if key.is_empty() {
+            i += 1;
+        } else {
+            attrs.push((key, value));
+        }
+    }
+}
+
+fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let ch = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => entity.strip_prefix('#').and_then(|num| {
+                    let code = match num.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => num.parse().ok(),
+                    };
+                    code.and_then(char::from_u32)
+                }),
+            };
+            ch.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[derive(Default)]
+struct Converter {
+    /// (ラベル, 本文)
+    footnotes: Vec<(String, String)>,
+}
+
+impl Converter {
+    fn blocks(&mut self, nodes: &[Node]) -> Vec<String> {
+        let mut out = Vec::new();
+        let mut inline_run: Vec<&Node> = Vec::new();
+        for node in nodes {
+            if node.is_block() {
+                self.flush_paragraph(&mut inline_run, &mut out);
+                if let Some(block) = self.block(node) {
+                    out.extend(block);
+                }
+            } else {
+                inline_run.push(node);
+            }
+        }
+        self.flush_paragraph(&mut inline_run, &mut out);
+        out
+    }
+
+    fn flush_paragraph(&mut self, run: &mut Vec<&Node>, out: &mut Vec<String>) {
+        if run.is_empty() {
+            return;
+        }
+        let text = self.inline_nodes(run.iter().copied());
+        run.clear();
+        let para = tidy_paragraph(&text);
+        if !para.is_empty() {
+            out.push(para);
+        }
+    }
+
+    /// ブロック要素 1 つ分（中身を展開する要素は複数ブロックになる）
+    fn block(&mut self, node: &Node) -> Option<Vec<String>> {
+        if let Some(source) = node.attr("data-math") {
+            return Some(vec![match source.trim() {
+                "" => "[数式]".to_string(),
+                source => format!("$$\n{source}\n$$"),
+            }]);
+        }
+        let one = |s: String| (!s.is_empty()).then(|| vec![s]);
+        match node.name() {
+            "p" | "summary" | "figcaption" => {
+                one(tidy_paragraph(&self.inline_nodes(node.children())))
+            }
+            h @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+                let level = h[1..].parse().unwrap_or(2);
+                let text = tidy_paragraph(&self.inline_nodes(node.children())).replace('\n', " ");
+                one(format!("{} {text}", "#".repeat(level)))
+            }
+            "hr" => one("---".to_string()),
+            "pre" => one(code_block(node)),
+            "ul" | "ol" => one(self.list(node)),
+            "table" => one(self.table(node)),
+            "blockquote" => {
+                let body = self.blocks(node.children()).join("\n\n");
+                one(quote(&body))
+            }
+            "section" if node.attr("role") == Some("doc-endnotes") => {
+                self.collect_footnotes(node);
+                None
+            }
+            "div" if node.has_class("callout") => {
+                let kind = if node.has_class("callout-warn") {
+                    "WARNING"
+                } else {
+                    "NOTE"
+                };
+                let body = self.blocks(node.children()).join("\n\n");
+                one(quote(&format!("[!{kind}]\n{body}")))
+            }
+            _ => Some(self.blocks(node.children())),
+        }
+    }
+
+    fn list(&mut self, node: &Node) -> String {
+        let ordered = node.name() == "ol";
+        let start: u32 = node.attr("start").and_then(|s| s.parse().ok()).unwrap_or(1);
+        let mut items = Vec::new();
+        let lis = node.children().iter().filter(|c| c.name() == "li");
+        for (n, li) in (start..).zip(lis) {
+            let marker = if ordered {
+                format!("{n}. ")
+            } else {
+                "- ".to_string()
+            };
+            let body = self.blocks(li.children()).join("\n\n");
+            let indent = " ".repeat(marker.len());
+            items.push(format!("{marker}{}", indent_continuation(&body, &indent)));
+        }
+        items.join("\n")
+    }
+
+    fn table(&mut self, node: &Node) -> String {
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        let mut stack: Vec<&Node> = node.children().iter().rev().collect();
+        while let Some(child) = stack.pop() {
+            match child.name() {
+                "thead" | "tbody" | "tfoot" => stack.extend(child.children().iter().rev()),
+                "tr" => {
+                    let cells = child
+                        .children()
+                        .iter()
+                        .filter(|c| matches!(c.name(), "td" | "th"))
+                        .map(|c| {
+                            self.blocks(c.children())
+                                .join("<br>")
+                                .replace("\\\n", "<br>")
+                                .replace('\n', "<br>")
+                                .replace('|', "\\|")
+                        })
+                        .collect();
+                    rows.push(cells);
+                }
+                _ => {}
+            }
+        }
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return String::new();
+        }
+        let line = |cells: &[String]| {
+            let mut padded: Vec<&str> = cells.iter().map(String::as_str).collect();
+            padded.resize(columns, "");
+            format!("| {} |", padded.join(" | "))
+        };
+        let mut out = vec![line(&rows[0]), format!("|{}", " --- |".repeat(columns))];
+        out.extend(rows[1..].iter().map(|r| line(r)));
+        out.join("\n")
+    }
+
+    fn collect_footnotes(&mut self, section: &Node) {
+        let mut items = Vec::new();
+        find_all(section, "li", &mut items);
+        for (idx, li) in items.into_iter().enumerate() {
+            let label = find_by_attr(li, "role", "doc-backlink")
+                .map(|a| a.text_content().trim().to_string())
+                .filter(|l| !l.is_empty())
+                .unwrap_or_else(|| (idx + 1).to_string());
+            let body = self.blocks(li.children()).join("\n\n");
+            self.footnotes.push((label, body));
+        }
+    }
+
+    fn inline_nodes<'a>(&mut self, nodes: impl IntoIterator<Item = &'a Node>) -> String {
+        nodes.into_iter().map(|n| self.inline(n)).collect()
+    }
+
+    fn inline(&mut self, node: &Node) -> String {
+        let Node::Element { name, children, .. } = node else {
+            let Node::Text(text) = node else {
+                unreachable!()
+            };
+            return escape_text(&collapse_whitespace(text));
+        };
+        if let Some(source) = node.attr("data-math") {
+            return match source.trim() {
+                "" => "[数式]".to_string(),
+                source => format!("${source}$"),
+            };
+        }
+        match name.as_str() {
+            "br" => "\\\n".to_string(),
+            "strong" | "b" => wrap_emphasis("**", &self.inline_nodes(children)),
+            "em" | "i" => wrap_emphasis("*", &self.inline_nodes(children)),
+            "del" | "s" => wrap_emphasis("~~", &self.inline_nodes(children)),
+            "code" => code_span(&node.text_content()),
+            "img" => match node.attr("alt").map(str::trim) {
+                Some(alt) if !alt.is_empty() => escape_text(alt),
+                _ => "[画像]".to_string(),
+            },
+            "a" => self.link(node),
+            "sup" | "sub" => {
+                let inner = self.inline_nodes(children);
+                if inner.starts_with("[^") || inner.is_empty() {
+                    inner
+                } else {
+                    format!("<{name}>{inner}</{name}>")
+                }
+            }
+            // アイコンなどの装飾。Typst のフレームは数式の出典が無いので置き換える
+            "svg" if node.has_class("typst-frame") => "[数式]".to_string(),
+            "svg" => String::new(),
+            _ => self.inline_nodes(children),
+        }
+    }
+
+    fn link(&mut self, node: &Node) -> String {
+        // 見出しのパーマリンクと脚注の戻りリンクは Markdown では要らない
+        if node.has_class("heading-anchor") || node.attr("role") == Some("doc-backlink") {
+            return String::new();
+        }
+        if node.attr("role") == Some("doc-noteref") {
+            return format!("[^{}]", node.text_content().trim());
+        }
+        let text = self.inline_nodes(node.children());
+        let text = text.trim();
+        let Some(href) = node.attr("href").filter(|h| !h.is_empty()) else {
+            return text.to_string();
+        };
+        if text.is_empty() {
+            return String::new();
+        }
+        if node.text_content().trim() == href && href.contains("://") {
+            return format!("<{href}>");
+        }
+        let href = href.replace(' ', "%20").replace(')', "%29");
+        format!("[{text}]({href})")
+    }
+}
+
+fn find_all<'a>(node: &'a Node, name: &str, out: &mut Vec<&'a Node>) {
+    for child in node.children() {
+        if child.name() == name {
+            out.push(child);
+        } else {
+            find_all(child, name, out);
+        }
+    }
+}
+
+fn find_by_attr<'a>(node: &'a Node, key: &str, value: &str) -> Option<&'a Node> {
+    node.children().iter().find_map(|child| {
+        if child.attr(key) == Some(value) {
+            Some(child)
+        } else {
+            find_by_attr(child, key, value)
+        }
+    })
+}
+
+fn code_block(pre: &Node) -> String {
+    let code = pre.children().iter().find(|c| c.name() == "code");
+    let lang = code
+        .and_then(|c| {
+            c.attr("data-lang").map(str::to_string).or_else(|| {
+                c.attr("class")?
+                    .split_whitespace()
+                    .find_map(|cls| cls.strip_prefix("language-"))
+                    .map(str::to_string)
+            })
+        })
+        .or_else(|| pre.attr("data-lang").map(str::to_string))
+        .unwrap_or_default();
+    let text = code.map_or_else(|| pre.text_content(), Node::text_content);
+    let text = text.trim_end_matches('\n');
+    let fence = "`".repeat(longest_run(text, '`').max(2) + 1);
+    format!("{fence}{}\n{text}\n{fence}", lang.trim())
+}
+
+fn code_span(text: &str) -> String {
+    let text = text.replace('\n', " ");
+    let ticks = "`".repeat(longest_run(&text, '`') + 1);
+    if text.starts_with('`') || text.ends_with('`') {
+        format!("{ticks} {text} {ticks}")
+    } else {
+        format!("{ticks}{text}{ticks}")
+    }
+}
+
+fn longest_run(text: &str, ch: char) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in text.chars() {
+        if c == ch {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    longest
+}
+
+/// 前後の空白は強調の外に出す（`** foo**` は強調にならない）
+fn wrap_emphasis(marker: &str, inner: &str) -> String {
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        return inner.to_string();
+    }
+    let lead = if inner.starts_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    let trail = if inner.ends_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    format!("{lead}{marker}{trimmed}{marker}{trail}")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() && c != '\u{a0}' {
+            if !in_space {
+                out.push(' ');
+            }
+            in_space = true;
+        } else {
+            out.push(c);
+            in_space = false;
+        }
+    }
+    out
+}
+
+fn escape_text(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let escape = match c {
+            '\\' | '`' | '*' | '[' | ']' | '<' => true,
+            // 単語の途中の _ は GFM では強調にならない
+            '_' => {
+                let word = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric());
+                !(word(i.checked_sub(1).and_then(|j| chars.get(j))) && word(chars.get(i + 1)))
+            }
+            _ => false,
+        };
+        if escape {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// 段落の前後・行頭の空白を落とし、行頭の記号が Markdown として解釈されないようにする
+fn tidy_paragraph(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.trim_start();
+            if line.starts_with(['#', '>', '-', '+', '|']) {
+                format!("\\{line}")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+fn quote(body: &str) -> String {
+    body.lines()
+        .map(|line| {
+            if line.is_empty() {
+                ">".to_string()
+            } else {
+                format!("> {line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 2 行目以降を字下げする（リストの項目や脚注の続き）
+fn indent_continuation(body: &str, indent: &str) -> String {
+    let mut lines = body.lines();
+    let mut out = lines.next().unwrap_or_default().to_string();
+    for line in lines {
+        out.push('\n');
+        if !line.is_empty() {
+            out.push_str(indent);
+            out.push_str(line);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_inline_markup() {
+        assert_eq!(
+            html_to_gfm("<p>a<strong> b </strong><em>c</em> <code>x`y</code> <del>d</del></p>"),
+            "a **b** *c* ``x`y`` ~~d~~"
+        );
+        assert_eq!(
+            html_to_gfm(
+                r#"<p><a href="https://e.com/a b">link</a> <a href="https://e.com">https://e.com</a></p>"#
+            ),
+            "[link](https://e.com/a%20b) <https://e.com>"
+        );
+    }
+
+    #[test]
+    fn escapes_markdown_in_text() {
+        assert_eq!(
+            html_to_gfm("<p># not a heading *x* [y] snake_case _z_</p>"),
+            r"\# not a heading \*x\* \[y\] snake_case \_z\_"
+        );
+        assert_eq!(html_to_gfm("<p>a &lt;b&gt; &amp;</p>"), r"a \<b> &");
+    }
+
+    #[test]
+    fn converts_blocks() {
+        assert_eq!(
+            html_to_gfm("<h2>Title<a class=\"heading-anchor\" href=\"#t\">#</a></h2><ol start=\"3\"><li>one</li><li><p>two</p><p>more</p></li></ol>"),
+            "## Title\n\n3. one\n4. two\n\n   more"
+        );
+        assert_eq!(
+            html_to_gfm("<pre><code class=\"language-rust\">fn main() {}\n</code></pre>"),
+            "```rust\nfn main() {}\n```"
+        );
+        assert_eq!(
+            html_to_gfm("<table><tr><th>a</th><th>b|c</th></tr><tr><td>1</td></tr></table>"),
+            "| a | b\\|c |\n| --- | --- |\n| 1 |  |"
+        );
+        assert_eq!(
+            html_to_gfm(r#"<div class="callout callout-warn"><p>careful</p></div>"#),
+            "> [!WARNING]\n> careful"
+        );
+    }
+
+    #[test]
+    fn collects_footnotes_at_the_end() {
+        let html = concat!(
+            r##"<p>text<sup><a role="doc-noteref" href="#fn1">1</a></sup></p>"##,
+            r##"<section role="doc-endnotes"><ol><li><a role="doc-backlink" href="#r1">1</a> note</li></ol></section>"##,
+        );
+        assert_eq!(html_to_gfm(html), "text[^1]\n\n[^1]: note");
+    }
+}
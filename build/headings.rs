@@ -0,0 +1,106 @@
+use crate::frontmatter::TocEntry;
+use regex::{Captures, Regex};
+use std::{collections::HashSet, sync::LazyLock};
+
+static HEADING_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<h([2-4])((?:\s[^>]*)?)>(.*?)</h[2-4]>").expect("valid regex")
+});
+static ID_ATTR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\bid\s*=\s*"([^"]*)""#).expect("valid regex"));
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new("<[^>]+>").expect("valid regex"));
+
+/// h2–h4 に id とパーマリンクを付け、見出しの木を返す
+///
+/// id は見出しの文字列から作る（日本語はそのまま残す）。Typst のラベルで
+/// 既に id が付いているものはそれを使う。同じ id は `-2`, `-3` … で区別する。
+pub fn anchor_headings(html: &str) -> (String, Vec<TocEntry>) {
+    let mut used: HashSet<String> = ID_ATTR_RE
+        .captures_iter(html)
+        .map(|c| c[1].to_string())
+        .collect();
+    let mut flat = Vec::new();
+    let out = HEADING_RE.replace_all(html, |caps: &Captures| {
+        let level: u8 = caps[1].parse().unwrap_or(2);
+        let attrs = &caps[2];
+        let inner = &caps[3];
+        let text = heading_text(inner);
+        let (id, attrs) = match ID_ATTR_RE.captures(attrs) {
+            Some(existing) => (existing[1].to_string(), attrs.to_string()),
+            None => {
+                let id = unique_id(&slugify(&text), &mut used);
+                let attrs = format!(r#"{attrs} id="{id}""#);
+                (id, attrs)
+            }
+        };
+        let anchor = format!(
+            r##"<a class="heading-anchor" href="#{id}" aria-label="この見出しへのリンク">#</a>"##
+        );
+        flat.push(TocEntry {
+            level,
+            id,
+            text,
+            children: Vec::new(),
+        });
+        format!("<h{level}{attrs}>{inner}{anchor}</h{level}>")
+    });
+    let out = out.into_owned();
+
+    let mut tree = Vec::new();
+    for entry in flat.into_iter().filter(|e| !e.text.is_empty()) {
+        insert_entry(&mut tree, entry);
+    }
+    (out, tree)
+}
+
+fn insert_entry(list: &mut Vec<TocEntry>, entry: TocEntry) {
+    match list.last_mut() {
+        Some(last) if last.level < entry.level => insert_entry(&mut last.children, entry),
+        _ => list.push(entry),
+    }
+}
+
+fn heading_text(inner: &str) -> String {
+    let text = TAG_RE.replace_all(inner, "");
+    let text = text
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 英数字（かな・漢字を含む）以外を `-` にまとめる
+fn slugify(text: &str) -> String {
+    let mut out = String::new();
+    let mut pending_dash = false;
+    for ch in text.chars().flat_map(char::to_lowercase) {
+        if ch.is_alphanumeric() || ch == '_' {
+            if pending_dash && !out.is_empty() {
+                out.push('-');
+            }
+            pending_dash = false;
+            out.push(ch);
+        } else {
+            pending_dash = true;
+        }
+    }
+    if out.is_empty() {
+        "section".to_string()
+    } else {
+        out
+    }
+}
+
+fn unique_id(base: &str, used: &mut HashSet<String>) -> String {
+    let mut id = base.to_string();
+    let mut n = 2;
+    while used.contains(&id) {
+        id = format!("{base}-{n}");
+        n += 1;
+    }
+    used.insert(id.clone());
+    id
+}
@@ -1,47 +1,46 @@
-use crate::frontmatter::FrontMatter;
+use crate::{frontmatter::FrontMatter, gfm};
 use anyhow::Result;
-use std::{
-    fs,
-    path::{Path, PathBuf},
-    process::Command,
-};
+use rayon::prelude::*;
+use std::{fs, path::PathBuf};
 
+/// 記事の HTML から Markdown（GFM + YAML フロントマター）を書き出す
+///
+/// 変換できなかった記事は警告を出して Markdown リンクを付けないだけにする。
 pub fn build_markdown(
     metas: &mut [FrontMatter],
     generated_md_dir: &str,
-    pandoc_filter: &str,
-) -> Result<bool> {
-    if !pandoc_available()? {
-        println!("cargo:warning=pandoc not found; skipping markdown generation");
-        return Ok(false);
-    }
-
+    site_url: &str,
+) -> Result<()> {
     let out_dir = PathBuf::from(generated_md_dir);
     fs::create_dir_all(&out_dir)?;
 
-    let mut all_ok = true;
-    for meta in metas.iter_mut() {
-        let slug = &meta.slug;
-        let html_rel = &meta.html;
-        let html_path = PathBuf::from("static").join(html_rel);
-
+    metas.par_iter_mut().for_each(|meta| {
+        let slug = meta.slug.clone();
         let md_rel = format!("generated/md/{slug}.md");
         let md_path = PathBuf::from("static").join(&md_rel);
+        let html_path = PathBuf::from("static").join(&meta.html);
 
-        if let Err(e) = run_pandoc_to_markdown(&html_path, &md_path, pandoc_filter) {
-            println!("cargo:warning=pandoc failed for {slug}: {e}");
-            all_ok = false;
-            break;
-        } else {
-            meta.markdown = Some(md_rel);
+        let result = fs::read_to_string(&html_path).and_then(|html| {
+            let markdown = format!(
+                "{}\n{}\n",
+                front_matter_yaml(meta, site_url),
+                gfm::html_to_gfm(&html)
+            );
+            // 変わっていなければ書かない（mtime を見るツール向け）
+            if fs::read_to_string(&md_path).ok().as_deref() == Some(markdown.as_str()) {
+                return Ok(());
+            }
+            fs::write(&md_path, markdown)
+        });
+        match result {
+            Ok(()) => meta.markdown = Some(md_rel),
+            Err(e) => {
+                println!("cargo:warning=markdown export failed for {slug}: {e}");
+                meta.markdown = None;
+            }
         }
-    }
-
-    if !all_ok {
-        Ok(false)
-    } else {
-        Ok(true)
-    }
+    });
+    Ok(())
 }
 
 pub fn write_index(metas: &[FrontMatter], generated_dir: &str) -> Result<()> {
@@ -54,47 +53,39 @@ pub fn write_index(metas: &[FrontMatter], generated_dir: &str) -> Result<()> {
     Ok(())
 }
 
-fn run_pandoc_to_markdown(html_path: &Path, md_path: &Path, pandoc_filter: &str) -> Result<()> {
-    if !html_path.exists() {
-        return Err(anyhow::anyhow!(
-            "source HTML not found: {}",
-            html_path.display()
-        ));
-    }
-    if let Some(parent) = md_path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-
-    let status = Command::new("pandoc")
-        .args([
-            "-f",
-            "html",
-            "-t",
-            "gfm",
-            "--wrap",
-            "none",
-            "--lua-filter",
-            pandoc_filter,
-            "-o",
-            md_path.to_str().unwrap(),
-            html_path.to_str().unwrap(),
-        ])
-        .status()
-        .map_err(|e| anyhow::anyhow!("pandoc invocation failed: {e}"))?;
-
-    if !status.success() {
-        return Err(anyhow::anyhow!(
-            "pandoc exited with status {status} for {}",
-            html_path.display()
-        ));
+/// 文字列は JSON の形で書く（YAML のダブルクォート文字列としてそのまま読める）
+fn front_matter_yaml(meta: &FrontMatter, site_url: &str) -> String {
+    let quote = |s: &str| serde_json::to_string(s).unwrap_or_else(|_| "\"\"".to_string());
+    let mut lines = vec!["---".to_string()];
+    let mut field = |key: &str, value: Option<&str>| {
+        if let Some(v) = value.filter(|v| !v.trim().is_empty()) {
+            lines.push(format!("{key}: {}", quote(v.trim())));
+        }
+    };
+    field("title", meta.title.as_deref());
+    field("subtitle", meta.subtitle.as_deref());
+    field(
+        "description",
+        meta.meta
+            .get("description")
+            .or_else(|| meta.meta.get("og:description"))
+            .map(String::as_str),
+    );
+    field("date", meta.published_at.as_deref());
+    field("updated", meta.updated_at.as_deref());
+    field("genre", meta.genre.as_deref());
+    field("series", meta.series.as_deref());
+    if let Some(order) = meta.series_order {
+        lines.push(format!("series_order: {order}"));
     }
-    Ok(())
-}
-
-fn pandoc_available() -> Result<bool> {
-    let status = Command::new("pandoc").arg("--version").status();
-    match status {
-        Ok(s) => Ok(s.success()),
-        Err(_) => Ok(false),
+    if !meta.tags.is_empty() {
+        let tags: Vec<String> = meta.tags.iter().map(|t| quote(t)).collect();
+        lines.push(format!("tags: [{}]", tags.join(", ")));
     }
+    lines.push(format!(
+        "url: {}",
+        quote(&format!("{site_url}/blog/{}", meta.slug))
+    ));
+    lines.push("---".to_string());
+    lines.join("\n")
 }
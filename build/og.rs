@@ -0,0 +1,141 @@
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+use typst_as_lib::{typst_kit_options::TypstKitFontOptions, TypstAsLibError, TypstEngine};
+use typst_library::{
+    layout::PagedDocument,
+    syntax::{FileId, Source, VirtualPath},
+};
+
+use crate::{
+    cache,
+    config::Site,
+    diagnostics::{self, CompileError, Diagnostic, Level, SourceLayout},
+    frontmatter::FrontMatter,
+};
+
+/// OG 画像のテンプレート（1200pt × 630pt のページを 1px/pt で PNG にする）
+pub const OG_TEMPLATE_PATH: &str = "static/og.typ";
+const OG_OUT_DIR: &str = "static/generated/og";
+const OG_URL_PREFIX: &str = "/assets/generated/og";
+const FONT_DIR: &str = "static/fonts";
+
+/// 記事ごとの OG 画像を書き出して `og_image` に URL を入れる
+///
+/// ファイル名に入力（テンプレート・タイトルなど）のハッシュを含めるので、
+/// 変わったものだけ作り直し、SNS 側のキャッシュも URL ごと切り替わる。
+/// `meta>og:image` を書いた記事には作らない。失敗しても警告にしてビルドは続ける。
+pub fn write_og_images(metas: &mut [FrontMatter], site: &Site) -> Result<()> {
+    let Ok(template) = fs::read_to_string(OG_TEMPLATE_PATH) else {
+        return Ok(());
+    };
+    let out_dir = PathBuf::from(OG_OUT_DIR);
+    fs::create_dir_all(&out_dir)?;
+    let fonts = load_site_fonts()?;
+
+    let written: Vec<Option<String>> = metas
+        .par_iter_mut()
+        .map(|meta| {
+            meta.og_image = None;
+            if meta.meta.contains_key("og:image") {
+                return None;
+            }
+            let prelude = card_prelude(meta, site);
+            let hash = cache::hash_bytes(format!("{prelude}{template}").as_bytes());
+            let name = format!("{}-{}.png", meta.slug, &hash[..12]);
+            let path = out_dir.join(&name);
+            if !path.exists() {
+                if let Err(e) = render_card(&prelude, &template, &fonts, &path) {
+                    let what = format!("OG image for `{}`", meta.slug);
+                    diagnostics::emit_as_warnings(&e, OG_TEMPLATE_PATH, &what);
+                    return None;
+                }
+            }
+            meta.og_image = Some(format!("{OG_URL_PREFIX}/{name}"));
+            Some(name)
+        })
+        .collect();
+
+    // 古いハッシュの画像や消えた記事の画像を消す
+    let keep: HashSet<String> = written.into_iter().flatten().collect();
+    for entry in fs::read_dir(&out_dir)? {
+        let entry = entry?;
+        if !keep.contains(entry.file_name().to_string_lossy().as_ref()) {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// テンプレートの前に差し込む変数（Rust の Debug 表記は Typst の文字列としても読める）
+fn card_prelude(meta: &FrontMatter, site: &Site) -> String {
+    let title = meta.title.as_deref().unwrap_or(meta.slug.as_str());
+    let subtitle = meta.subtitle.as_deref().unwrap_or_default();
+    let date = meta.published_at.as_deref().unwrap_or_default();
+    let tags: String = meta.tags.iter().map(|t| format!("{t:?}, ")).collect();
+    let author = &site.author;
+    format!(
+        "#let og-title = {title:?}\n#let og-subtitle = {subtitle:?}\n#let og-tags = ({tags})\n#let og-date = {date:?}\n#let og-author = {author:?}\n"
+    )
+}
+
+fn render_card(prelude: &str, template: &str, fonts: &[Vec<u8>], path: &Path) -> Result<()> {
+    let layout = SourceLayout {
+        file: OG_TEMPLATE_PATH,
+        preamble_file: OG_TEMPLATE_PATH,
+        preamble_lines: 0,
+        body_start: prelude.matches('\n').count(),
+    };
+    let main = Source::new(
+        FileId::new(None, VirtualPath::new("og.typ")),
+        format!("{prelude}{template}"),
+    );
+    let engine = TypstEngine::builder()
+        .fonts(fonts.iter().map(Vec::as_slice))
+        .search_fonts_with(TypstKitFontOptions::default())
+        .main_file(main.clone())
+        .build();
+    let doc = engine
+        .compile::<PagedDocument>()
+        .output
+        .map_err(|e| match e {
+            TypstAsLibError::TypstSource(diags) => CompileError(
+                diags
+                    .iter()
+                    .map(|d| Diagnostic::from_typst(d, &main, &layout))
+                    .collect(),
+            ),
+            other => CompileError(vec![Diagnostic::plain(
+                Level::Error,
+                OG_TEMPLATE_PATH,
+                other.to_string(),
+            )]),
+        })?;
+    let page = doc
+        .pages
+        .first()
+        .ok_or_else(|| anyhow!("{OG_TEMPLATE_PATH} produced no pages"))?;
+    let png = typst_render::render(page, 1.0).encode_png()?;
+    fs::write(path, png)?;
+    Ok(())
+}
+
+/// static/fonts のフォント（OG 画像と PDF で使う）
+pub fn load_site_fonts() -> Result<Vec<Vec<u8>>> {
+    let mut fonts = Vec::new();
+    for entry in fs::read_dir(FONT_DIR)? {
+        let path = entry?.path();
+        let ext = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        if matches!(ext, "ttf" | "otf") {
+            fonts.push(fs::read(&path)?);
+        }
+    }
+    Ok(fonts)
+}
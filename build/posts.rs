@@ -1,15 +1,35 @@
-use anyhow::{anyhow, Result};
-use itertools::Itertools;
+use anyhow::Result;
+use chrono::{Offset, Utc};
 #[cfg(not(debug_assertions))]
 use minify_html::{minify, Cfg as HtmlMinCfg};
 use rayon::prelude::*;
 use regex::Regex;
-use std::{collections::HashMap, fs, path::PathBuf, sync::LazyLock};
-use typst_as_lib::{typst_kit_options::TypstKitFontOptions, TypstEngine};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+use typst_as_lib::{typst_kit_options::TypstKitFontOptions, TypstAsLibError, TypstEngine};
 use typst_html::HtmlDocument;
-use typst_library::diag::SourceDiagnostic;
-
-use crate::frontmatter::FrontMatter;
+use typst_library::{
+    diag::SourceDiagnostic,
+    layout::PagedDocument,
+    syntax::{FileId, Source, VirtualPath},
+};
+use typst_pdf::PdfOptions;
+
+use crate::{
+    cache::{self, BuildCache, CachedPost},
+    cards,
+    config::Site,
+    content::{self, SlugStyle},
+    diagnostics::{self, CompileError, Diagnostic, Level, SourceLayout},
+    frontmatter::{parse_datetime_in, FrontMatter, PostStatus, PRIVATE_DIR},
+    headings, og,
+    resolver::AssetResolver,
+    responsive::ImagePipeline,
+};
 
 // 静的Regex（毎回コンパイルを避ける）
 static META_RE: LazyLock<Regex> =
@@ -18,61 +38,259 @@ static BODY_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new("(?is)<body[^>]*>(.*?)</body>").expect("valid regex"));
 static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new("<[^>]+>").expect("valid regex"));
 
-pub fn build_posts(preamble_path: &str, generated_dir: &str) -> Result<Vec<FrontMatter>> {
+/// PDF 版のページ設定と表題（無ければ PDF は作らない）
+pub const PRINT_TEMPLATE_PATH: &str = "static/print.typ";
+
+pub fn build_posts(
+    preamble_path: &str,
+    generated_dir: &str,
+    assets: &AssetResolver,
+    site: &Site,
+) -> Result<Vec<FrontMatter>> {
     let out_dir = PathBuf::from(generated_dir);
     fs::create_dir_all(&out_dir)?;
-    let binaries = load_binary_assets()?;
-    let preamble = load_preamble(preamble_path);
-
-    // エントリを収集
-    let entries: Vec<_> = fs::read_dir("content")?
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("typ"))
-        .filter(|e| {
-            e.path()
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .map(|s| !s.starts_with('_'))
-                .unwrap_or(false)
-        })
-        .collect();
+    let preamble = load_preamble(preamble_path, site);
+    let print = fs::read_to_string(PRINT_TEMPLATE_PATH).ok();
+    let fonts = match print {
+        Some(_) => og::load_site_fonts()?,
+        None => Vec::new(),
+    };
+
+    // 本文の <img> を <picture> にするので、先にサイト共通の画像とバンドルの画像を処理しておく
+    let images = ImagePipeline::load();
+    images.process_site_images()?;
+
+    // エントリを収集（content/2025/foo.typ のような入れ子や content/<slug>/index.typ も）
+    let entries = content::collect_sources(Path::new("content"), SlugStyle::from_env())?;
+
+    // 下書きはプレビュー専用に static の外へ（/assets からは読めないように）
+    let private_dir = PathBuf::from(PRIVATE_DIR);
+    let drafts_dir = private_dir.join("drafts");
+    fs::create_dir_all(&drafts_dir)?;
+    remove_legacy_drafts(&out_dir)?;
+
+    // ソース・参照アセットが変わっていない記事は前回の結果を使い回す
+    // （メタの既定値と PDF の著者名は rodin.toml から来るので [site] もキーに入れる）
+    let build_cache = BuildCache::load(
+        generated_dir,
+        &format!(
+            "{}{}{}",
+            preamble.text,
+            print.as_deref().unwrap_or_default(),
+            serde_json::to_string(site)?
+        ),
+    );
 
-    // 並列コンパイル
-    let results: Vec<Result<FrontMatter>> = entries
+    // 並列コンパイル（コンパイルできなかった下書きは None、bool は再利用したか）
+    let results: Vec<Result<Option<(CachedPost, bool)>>> = entries
         .par_iter()
         .map(|entry| {
-            let slug = entry
-                .path()
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap()
-                .to_string();
-            let raw = fs::read_to_string(entry.path())?;
-            let (meta, body) = parse_front_matter(&slug, &raw);
+            let slug = entry.slug.clone();
+            let file = entry.display_path();
+            let raw = fs::read_to_string(&entry.path)?;
+            let source_hash = cache::hash_bytes(raw.as_bytes());
+            let mut referenced = cache::referenced_assets(&raw, assets);
+            // バンドル内のファイルは参照の有無に関係なく全部見る（サブの include もあるので）
+            let bundle_files = match &entry.bundle_dir {
+                Some(dir) => {
+                    let files = content::bundle_files(dir)?;
+                    referenced.extend(content::hash_bundle(dir, &files)?);
+                    files
+                }
+                None => Vec::new(),
+            };
+            let public_assets: Vec<String> = bundle_files
+                .into_iter()
+                .filter(|rel| !rel.ends_with(".typ"))
+                .collect();
+            let (meta, body) = parse_front_matter(&slug, &file, &raw, site);
+            let is_draft = meta.status == PostStatus::Draft;
+            // 下書きのアセットはトークンを確かめるプレビューの URL から読ませる
+            let asset_base = if is_draft {
+                format!("/preview/{slug}")
+            } else {
+                format!("/blog/{slug}")
+            };
+            // キャッシュヒットでもマニフェストに載せないと生成物が消されてしまう
+            if let Some(dir) = &entry.bundle_dir {
+                images.process_dir(dir, &asset_base)?;
+            }
+
+            let hit = build_cache
+                .lookup(&slug, &source_hash, &referenced)
+                .filter(|hit| hit.meta.source.as_deref() == Some(file.as_str()))
+                .filter(|hit| {
+                    hit.meta
+                        .pdf
+                        .as_ref()
+                        .is_none_or(|pdf| Path::new("static").join(pdf).exists())
+                });
+            if let Some(hit) = hit {
+                // 警告は前回の分を出し直す（--deny-warnings で見落とさないように）
+                hit.warnings.iter().for_each(diagnostics::emit);
+                let cached = hit.clone();
+                if let Some(dir) = &entry.bundle_dir {
+                    content::copy_bundle_assets(&slug, dir, &public_assets, is_draft)?;
+                }
+                return Ok(Some((cached, true)));
+            }
+
             let body_clean = strip_preamble_import(&body);
-            let html = compile_typst(&preamble, &body_clean, &binaries)?;
+            let compiled = compile_typst(
+                &preamble,
+                &file,
+                "",
+                &body_clean,
+                assets,
+                entry.bundle_dir.as_deref(),
+            );
+            let (html, warnings) = match compiled {
+                Ok(compiled) => compiled,
+                // 書きかけの下書きでビルド全体を落とさない
+                Err(e) if is_draft => {
+                    println!("cargo:warning=draft {slug} failed to compile; skipping preview");
+                    diagnostics::emit_error(&e);
+                    return Ok(None);
+                }
+                Err(e) => return Err(e),
+            };
 
-            let html_path = out_dir.join(format!("{slug}.html"));
+            let (html_path, html_rel) = if is_draft {
+                (
+                    drafts_dir.join(format!("{slug}.html")),
+                    format!("drafts/{slug}.html"),
+                )
+            } else {
+                (
+                    out_dir.join(format!("{slug}.html")),
+                    format!("generated/{slug}.html"),
+                )
+            };
+            let html = content::rewrite_asset_links(&html, &asset_base, &public_assets);
+            let html = images.rewrite_html(&html);
+            let (html, toc) = headings::anchor_headings(&html);
             fs::write(&html_path, maybe_minify_html(html.clone()))?;
+            if let Some(dir) = &entry.bundle_dir {
+                content::copy_bundle_assets(&slug, dir, &public_assets, is_draft)?;
+            }
 
             let mut meta_out = meta.clone();
-            meta_out.html = format!("generated/{slug}.html");
+            meta_out.html = html_rel;
             meta_out.reading_minutes = Some(estimate_reading_minutes(&html));
-            Ok(meta_out)
+            meta_out.source = Some(file.clone());
+            meta_out.toc = toc;
+            if let (Some(print), false) = (&print, is_draft) {
+                let pdf_layout = PdfLayout {
+                    template: print,
+                    fonts: &fonts,
+                    out_dir: &out_dir,
+                    site,
+                };
+                meta_out.pdf = write_pdf(
+                    &preamble,
+                    &pdf_layout,
+                    &meta,
+                    &file,
+                    &body_clean,
+                    assets,
+                    entry.bundle_dir.as_deref(),
+                );
+            }
+            let compiled = CachedPost {
+                source_hash,
+                assets: referenced,
+                meta: meta_out,
+                warnings,
+            };
+            Ok(Some((compiled, false)))
         })
         .collect();
 
     // エラーチェックと収集
     let mut index = Vec::with_capacity(results.len());
+    let mut drafts = Vec::new();
+    let mut cached_posts = HashMap::with_capacity(results.len());
+    let mut reused = 0;
     for result in results {
-        index.push(result?);
+        let Some((post, hit)) = result? else {
+            continue;
+        };
+        if hit {
+            reused += 1;
+        }
+        if post.meta.status == PostStatus::Draft {
+            drafts.push(post.meta.clone());
+        } else {
+            index.push(post.meta.clone());
+        }
+        cached_posts.insert(post.meta.slug.clone(), post);
     }
-
-    println!("cargo:warning=generated {} posts", index.len());
+    // キャッシュから戻した記事もテンプレートの変更を拾えるよう毎回確認する
+    og::write_og_images(&mut index, site)?;
+    let bundled: HashSet<&str> = index.iter().map(|m| m.slug.as_str()).collect();
+    content::prune_bundle_assets(&bundled, false)?;
+    let draft_bundled: HashSet<&str> = drafts.iter().map(|m| m.slug.as_str()).collect();
+    content::prune_bundle_assets(&draft_bundled, true)?;
+    build_cache.save(generated_dir, cached_posts)?;
+    images.save()?;
+    prune_stale_drafts(&drafts_dir, &drafts)?;
+    // 下書きは index.json に載せず、プレビュー用の drafts.json にだけ書く
+    fs::write(
+        private_dir.join("drafts.json"),
+        serde_json::to_string_pretty(&drafts)?,
+    )?;
+
+    println!(
+        "cargo:warning=generated {} posts ({} drafts for preview, {} reused from cache)",
+        index.len(),
+        drafts.len(),
+        reused
+    );
     Ok(index)
 }
 
-pub fn build_home(preamble_path: &str, generated_dir: &str) -> Result<()> {
+/// 以前のビルドが static/generated に書いた下書きを消す
+fn remove_legacy_drafts(out_dir: &Path) -> Result<()> {
+    let dir = out_dir.join("drafts");
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    let list = out_dir.join("drafts.json");
+    if list.exists() {
+        fs::remove_file(list)?;
+    }
+    Ok(())
+}
+
+/// 下書きでなくなった・消えた記事の HTML をプレビュー用ディレクトリから消す
+fn prune_stale_drafts(drafts_dir: &Path, drafts: &[FrontMatter]) -> Result<()> {
+    let keep: HashSet<String> = drafts.iter().map(|m| format!("{}.html", m.slug)).collect();
+    for entry in fs::read_dir(drafts_dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !keep.contains(&name) {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// 一覧・サイトマップ・フィードに載せる記事（限定公開と公開前の予約投稿を除く）
+pub fn listed_posts(metas: &[FrontMatter]) -> Vec<FrontMatter> {
+    let now = Utc::now();
+    metas
+        .iter()
+        .filter(|m| m.is_listed_at(now))
+        .cloned()
+        .collect()
+}
+
+pub fn build_home(
+    preamble_path: &str,
+    generated_dir: &str,
+    assets: &AssetResolver,
+    site: &Site,
+) -> Result<()> {
     let path = PathBuf::from("content/_home.typ");
     if !path.exists() {
         return Ok(());
@@ -81,24 +299,36 @@ pub fn build_home(preamble_path: &str, generated_dir: &str) -> Result<()> {
     let out_dir = PathBuf::from(generated_dir);
     fs::create_dir_all(&out_dir)?;
 
-    let binaries = load_binary_assets()?;
-    let preamble = load_preamble(preamble_path);
+    let preamble = load_preamble(preamble_path, site);
     let raw = fs::read_to_string(&path)?;
     let body_clean = strip_preamble_import(&raw);
 
     // typst 側での取得が面倒すぎるので Rust でやってプレースホルダーにぶち込む
     let index_path = PathBuf::from(generated_dir).join("index.json");
-    let index_bytes = fs::read(&index_path)?;
-    let metas: Vec<FrontMatter> = serde_json::from_slice(&index_bytes)?;
-    let cards_html = build_cards_html(&metas);
+    let metas: Vec<FrontMatter> = serde_json::from_slice(&fs::read(&index_path)?)?;
+    // 限定公開と公開前の予約投稿はテンプレートにも渡さない
+    let listed = listed_posts(&metas);
+    let listed_json = serde_json::to_string(&listed)?;
+    let cards_html = cards::post_cards_html(&listed);
     let injected = format!(
-        "#let __posts_items = json(\"static/generated/index.json\")\n#let __posts_list_html = raw({cards_html:?}, lang: \"html\")\n{body_clean}"
+        "#let __posts_items = json(bytes({listed_json:?}))\n#let __posts_list_html = raw({cards_html:?}, lang: \"html\")\n"
     );
 
-    let mut html = compile_typst(&preamble, &injected, &binaries)?;
+    let (mut html, _) = compile_typst(
+        &preamble,
+        "content/_home.typ",
+        &injected,
+        &body_clean,
+        assets,
+        None,
+    )?;
+    // カードは予約投稿の公開に合わせてサーバーが差し込む
     html = html.replace(
         "<p>POSTS_LIST_PLACEHOLDER</p>",
-        &format!("<div class=\"posts-list\">{}</div>", &cards_html),
+        &format!(
+            "<div class=\"posts-list\">{}</div>",
+            cards::POSTS_LIST_TOKEN
+        ),
     );
 
     let html_path = out_dir.join("home.html");
@@ -107,7 +337,12 @@ pub fn build_home(preamble_path: &str, generated_dir: &str) -> Result<()> {
     Ok(())
 }
 
-pub fn build_profile(preamble_path: &str, generated_dir: &str) -> Result<Option<FrontMatter>> {
+pub fn build_profile(
+    preamble_path: &str,
+    generated_dir: &str,
+    assets: &AssetResolver,
+    site: &Site,
+) -> Result<Option<FrontMatter>> {
     let path = PathBuf::from("content/_profile.typ");
     if !path.exists() {
         return Ok(None);
@@ -116,13 +351,19 @@ pub fn build_profile(preamble_path: &str, generated_dir: &str) -> Result<Option<
     let out_dir = PathBuf::from(generated_dir);
     fs::create_dir_all(&out_dir)?;
 
-    let binaries = load_binary_assets()?;
-    let preamble = load_preamble(preamble_path);
+    let preamble = load_preamble(preamble_path, site);
     let raw = fs::read_to_string(&path)?;
-    let (mut meta, body) = parse_front_matter("_profile", &raw);
+    let (mut meta, body) = parse_front_matter("_profile", "content/_profile.typ", &raw, site);
     let body_clean = strip_preamble_import(&body);
 
-    let html = compile_typst(&preamble, &body_clean, &binaries)?;
+    let (html, _) = compile_typst(
+        &preamble,
+        "content/_profile.typ",
+        "",
+        &body_clean,
+        assets,
+        None,
+    )?;
     let html_path = out_dir.join("profile.html");
     fs::write(&html_path, maybe_minify_html(html.clone()))?;
 
@@ -136,7 +377,12 @@ pub fn build_profile(preamble_path: &str, generated_dir: &str) -> Result<Option<
     Ok(Some(meta))
 }
 
-pub fn build_pgp(preamble_path: &str, generated_dir: &str) -> Result<Option<FrontMatter>> {
+pub fn build_pgp(
+    preamble_path: &str,
+    generated_dir: &str,
+    assets: &AssetResolver,
+    site: &Site,
+) -> Result<Option<FrontMatter>> {
     let path = PathBuf::from("content/_pgp.typ");
     if !path.exists() {
         return Ok(None);
@@ -145,13 +391,12 @@ pub fn build_pgp(preamble_path: &str, generated_dir: &str) -> Result<Option<Fron
     let out_dir = PathBuf::from(generated_dir);
     fs::create_dir_all(&out_dir)?;
 
-    let binaries = load_binary_assets()?;
-    let preamble = load_preamble(preamble_path);
+    let preamble = load_preamble(preamble_path, site);
     let raw = fs::read_to_string(&path)?;
-    let (mut meta, body) = parse_front_matter("_pgp", &raw);
+    let (mut meta, body) = parse_front_matter("_pgp", "content/_pgp.typ", &raw, site);
     let body_clean = strip_preamble_import(&body);
 
-    let html = compile_typst(&preamble, &body_clean, &binaries)?;
+    let (html, _) = compile_typst(&preamble, "content/_pgp.typ", "", &body_clean, assets, None)?;
     let html_path = out_dir.join("pgp.html");
     fs::write(&html_path, maybe_minify_html(html.clone()))?;
 
@@ -160,7 +405,7 @@ pub fn build_pgp(preamble_path: &str, generated_dir: &str) -> Result<Option<Fron
     meta.slug = "pgp".to_string();
     meta.meta
         .entry("link:canonical".to_string())
-        .or_insert_with(|| "https://suzuneu.com/pgp".to_string());
+        .or_insert_with(|| format!("{}/pgp", site.url));
 
     let meta_path = out_dir.join("pgp_meta.json");
     fs::write(&meta_path, serde_json::to_string_pretty(&meta)?)?;
@@ -169,14 +414,44 @@ pub fn build_pgp(preamble_path: &str, generated_dir: &str) -> Result<Option<Fron
     Ok(Some(meta))
 }
 
-fn parse_front_matter(slug: &str, source: &str) -> (FrontMatter, String) {
+/// `parse_front_matter` が読むキー（`meta>` で始まるものは別扱い）
+///
+/// `rodin-content check` もこの一覧で未知のキーを報告する。
+pub const FRONT_MATTER_KEYS: &[&str] = &[
+    "title",
+    "subtitle",
+    "genre",
+    "tags",
+    "breadcrumbs",
+    "date",
+    "updated",
+    "status",
+    "publish_at",
+    "toc",
+    "series",
+    "series_order",
+];
+
+fn parse_front_matter(slug: &str, file: &str, source: &str, site: &Site) -> (FrontMatter, String) {
     let mut fm = FrontMatter {
         slug: slug.to_string(),
         ..Default::default()
     };
     let mut meta_map: HashMap<String, String> = HashMap::new();
     let mut body_lines = Vec::new();
-    for line in source.lines() {
+    // オフセットの無い publish_at はサイトのタイムゾーンで読む（rodin.toml の読み込み時に検証済み）
+    let site_offset = site.timezone.parse().unwrap_or_else(|_| Utc.fix());
+    let mut invalid_publish_at = false;
+    // 読めなかった値は Typst の警告と同じく diagnostics から出す（rodin-content が数える）
+    let warn = |idx: usize, message: String| {
+        diagnostics::emit(&Diagnostic {
+            line: Some(idx + 1),
+            ..Diagnostic::plain(Level::Warning, file, message)
+        })
+    };
+    for (idx, line) in source.lines().enumerate() {
+        // フロントマターの行も Typst のコメントとして残す（診断の行番号を元ファイルと揃える）
+        body_lines.push(line);
         if let Some(rest) = line.strip_prefix("//:") {
             let trimmed = rest.trim();
             if let Some(val) = trimmed.strip_prefix("title:") {
@@ -215,6 +490,58 @@ fn parse_front_matter(slug: &str, source: &str) -> (FrontMatter, String) {
                 fm.updated_at = Some(val.trim().to_string());
                 continue;
             }
+            if let Some(val) = trimmed.strip_prefix("status:") {
+                fm.status = val.parse().unwrap_or_else(|()| {
+                    warn(
+                        idx,
+                        format!("unknown status `{}`; treating as draft", val.trim()),
+                    );
+                    PostStatus::Draft
+                });
+                continue;
+            }
+            if let Some(val) = trimmed.strip_prefix("publish_at:") {
+                // 読めない日時で予約が外れて即公開にならないよう、下書きに倒す
+                match parse_datetime_in(val, site_offset) {
+                    Some(at) => fm.publish_at = Some(at.to_rfc3339()),
+                    None => {
+                        warn(
+                            idx,
+                            format!(
+                                "publish_at `{}` is not a date (e.g. 2025-01-31 or 2025-01-31T09:00); treating as draft",
+                                val.trim()
+                            ),
+                        );
+                        invalid_publish_at = true;
+                    }
+                }
+                continue;
+            }
+            if let Some(val) = trimmed.strip_prefix("toc:") {
+                match val.trim().to_ascii_lowercase().as_str() {
+                    "false" | "no" | "off" => fm.hide_toc = true,
+                    "true" | "yes" | "on" => fm.hide_toc = false,
+                    other => warn(idx, format!("toc must be true or false: `{other}`")),
+                }
+                continue;
+            }
+            if let Some(val) = trimmed.strip_prefix("series:") {
+                let id = val.trim();
+                if !id.is_empty() {
+                    fm.series = Some(id.to_string());
+                }
+                continue;
+            }
+            if let Some(val) = trimmed.strip_prefix("series_order:") {
+                match val.trim().parse() {
+                    Ok(order) if order > 0 => fm.series_order = Some(order),
+                    _ => warn(
+                        idx,
+                        format!("series_order must be a positive integer: `{}`", val.trim()),
+                    ),
+                }
+                continue;
+            }
             if let Some(val) = trimmed.strip_prefix("meta>") {
                 // format: meta>key: value
                 if let Some((k, v)) = val.split_once(':') {
@@ -223,11 +550,23 @@ fn parse_front_matter(slug: &str, source: &str) -> (FrontMatter, String) {
                     if !key.is_empty() && !val.is_empty() {
                         meta_map.insert(key, val);
                     }
-                    continue;
                 }
+                continue;
+            }
+            // ここまでで読まなかった行（キーの打ち間違いや `title :` など）は知らせる
+            let key = trimmed.split_once(':').map_or(trimmed, |(k, _)| k).trim();
+            if !key.is_empty() {
+                let hint = if FRONT_MATTER_KEYS.contains(&key) {
+                    "write it as `key: value`"
+                } else {
+                    "unknown key"
+                };
+                warn(idx, format!("front matter `{trimmed}` ignored ({hint})"));
             }
         }
-        body_lines.push(line);
+    }
+    if invalid_publish_at {
+        fm.status = PostStatus::Draft;
     }
     if let Some(title) = fm.title.as_ref() {
         meta_map
@@ -241,19 +580,19 @@ fn parse_front_matter(slug: &str, source: &str) -> (FrontMatter, String) {
     // デフォルト.
     meta_map
         .entry("author".to_string())
-        .or_insert_with(|| "すずねーう".to_string());
-    meta_map
-        .entry("link:author".to_string())
-        .or_insert_with(|| "https://twitter.com/suzuneu_discord".to_string());
+        .or_insert_with(|| site.author.clone());
+    if let Some(url) = site.twitter_url() {
+        meta_map.entry("link:author".to_string()).or_insert(url);
+    }
     meta_map
         .entry("referrer".to_string())
         .or_insert_with(|| "strict-origin-when-cross-origin".to_string());
     meta_map
         .entry("og:site_name".to_string())
-        .or_insert_with(|| "すずねーうのウェブサイト".to_string());
+        .or_insert_with(|| site.og_site_name.clone());
     meta_map
         .entry("og:locale".to_string())
-        .or_insert_with(|| "ja_JP".to_string());
+        .or_insert_with(|| site.locale.clone());
     meta_map
         .entry("og:type".to_string())
         .or_insert_with(|| "article".to_string());
@@ -275,44 +614,165 @@ fn parse_front_matter(slug: &str, source: &str) -> (FrontMatter, String) {
     (fm, body_lines.join("\n"))
 }
 
+/// `content/<slug>.typ` などをプリアンブル付きでコンパイルする
+///
+/// `prelude` は Rust 側で本文の前に差し込むコード（改行で終わること）。
+/// 診断は `file` の行・列に戻して出し、警告は HTML と一緒に返す。
 fn compile_typst(
-    preamble: &str,
-    typst_source: &str,
-    binaries: &[(String, Vec<u8>)],
-) -> Result<String> {
-    let combined = format!("{preamble}\n{typst_source}");
-    dbg!(
-        "Binary assets loaded:",
-        binaries.iter().map(|(p, _)| p).collect::<Vec<_>>()
-    );
-    let engine = TypstEngine::builder()
-        .search_fonts_with(TypstKitFontOptions::default())
-        .with_static_file_resolver(
-            binaries
-                .iter()
-                .map(|(p, b)| (p.as_str(), b.as_slice()))
-                .collect::<Vec<_>>(),
-        )
-        .main_file(combined)
+    preamble: &Preamble,
+    file: &str,
+    prelude: &str,
+    body: &str,
+    assets: &AssetResolver,
+    bundle_dir: Option<&Path>,
+) -> Result<(String, Vec<Diagnostic>)> {
+    let combined = format!("{}\n{prelude}{body}", preamble.text);
+    let preamble_lines = preamble.text.matches('\n').count() + 1;
+    let layout = SourceLayout {
+        file,
+        preamble_file: &preamble.path,
+        preamble_lines,
+        body_start: preamble_lines + prelude.matches('\n').count(),
+    };
+    // スパンを行・列に戻すため、メインのソースはこちらで持っておく
+    let main = Source::new(FileId::new(None, VirtualPath::new("main.typ")), combined);
+    let mut builder = TypstEngine::builder().search_fonts_with(TypstKitFontOptions::default());
+    // バンドルの画像・データ・サブの .typ は記事からの相対パスで読めるようにする
+    if let Some(dir) = bundle_dir {
+        builder = builder.with_file_system_resolver(dir);
+    }
+    // 見つからなかったときのエラーはこちらのものが出るよう最後に置く
+    let engine = builder
+        .add_file_resolver(assets.clone())
+        .main_file(main.clone())
         .build();
 
     let result = engine.compile::<HtmlDocument>();
-    if !result.warnings.is_empty() {
-        for w in &result.warnings {
-            println!("cargo:warning=Typst warning: {}", w.message);
+    let warnings: Vec<Diagnostic> = result
+        .warnings
+        .iter()
+        .map(|w| Diagnostic::from_typst(w, &main, &layout))
+        .collect();
+    warnings.iter().for_each(diagnostics::emit);
+    let doc = result.output.map_err(|e| match e {
+        TypstAsLibError::TypstSource(diags) => compile_error(&diags, &main, &layout),
+        other => CompileError(vec![Diagnostic::plain(
+            Level::Error,
+            file,
+            other.to_string(),
+        )]),
+    })?;
+    let html = typst_html::html(&doc).map_err(|diags| compile_error(&diags, &main, &layout))?;
+    Ok((postprocess_typst_html(&html), warnings))
+}
+
+/// PDF 版に使うテンプレートとフォント、書き出し先
+struct PdfLayout<'a> {
+    template: &'a str,
+    fonts: &'a [Vec<u8>],
+    out_dir: &'a Path,
+    site: &'a Site,
+}
+
+/// 記事を PDF にして `generated/<slug>.pdf` に書く
+///
+/// HTML 用の関数が紙で使えないなど PDF だけ失敗したときは警告にして None を返す。
+fn write_pdf(
+    preamble: &Preamble,
+    layout: &PdfLayout,
+    meta: &FrontMatter,
+    file: &str,
+    body: &str,
+    assets: &AssetResolver,
+    bundle_dir: Option<&Path>,
+) -> Option<String> {
+    let title = meta.title.as_deref().unwrap_or(meta.slug.as_str());
+    let subtitle = meta.subtitle.as_deref().unwrap_or_default();
+    let date = meta.published_at.as_deref().unwrap_or_default();
+    let tags: String = meta.tags.iter().map(|t| format!("{t:?}, ")).collect();
+    let site = layout.site;
+    let url = format!("{}/blog/{}", site.url, meta.slug);
+    let (author, site_name) = (&site.author, &site.name);
+    let prelude = format!(
+        "#let post-title = {title:?}\n#let post-subtitle = {subtitle:?}\n#let post-date = {date:?}\n#let post-tags = ({tags})\n#let post-url = {url:?}\n#let post-author = {author:?}\n#let site-name = {site_name:?}\n{}\n",
+        layout.template
+    );
+    let result = compile_pdf(
+        preamble,
+        file,
+        &prelude,
+        body,
+        assets,
+        bundle_dir,
+        layout.fonts,
+    )
+    .and_then(|pdf| {
+        fs::write(layout.out_dir.join(format!("{}.pdf", meta.slug)), pdf)?;
+        Ok(())
+    });
+    match result {
+        Ok(()) => Some(format!("generated/{}.pdf", meta.slug)),
+        Err(e) => {
+            let what = format!("PDF for `{}` skipped", meta.slug);
+            diagnostics::emit_as_warnings(&e, file, &what);
+            None
         }
     }
-    let doc = result.output.map_err(|e| anyhow!(e.to_string()))?;
-    let html = typst_html::html(&doc).map_err(|diags| anyhow!(format_diagnostics(&diags)))?;
-    Ok(postprocess_typst_html(&html))
 }
 
-fn format_diagnostics(diags: &[SourceDiagnostic]) -> String {
-    diags
-        .iter()
-        .map(|d| d.message.as_str())
-        .collect::<Vec<_>>()
-        .join("; ")
+/// `compile_typst` の PDF 版。警告は HTML のときと同じなので出さない
+fn compile_pdf(
+    preamble: &Preamble,
+    file: &str,
+    prelude: &str,
+    body: &str,
+    assets: &AssetResolver,
+    bundle_dir: Option<&Path>,
+    fonts: &[Vec<u8>],
+) -> Result<Vec<u8>> {
+    let combined = format!("{}\n{prelude}{body}", preamble.text);
+    let preamble_lines = preamble.text.matches('\n').count() + 1;
+    let layout = SourceLayout {
+        file,
+        preamble_file: &preamble.path,
+        preamble_lines,
+        body_start: preamble_lines + prelude.matches('\n').count(),
+    };
+    let main = Source::new(FileId::new(None, VirtualPath::new("main.typ")), combined);
+    let mut builder = TypstEngine::builder()
+        .fonts(fonts.iter().map(Vec::as_slice))
+        .search_fonts_with(TypstKitFontOptions::default());
+    if let Some(dir) = bundle_dir {
+        builder = builder.with_file_system_resolver(dir);
+    }
+    let engine = builder
+        .add_file_resolver(assets.clone())
+        .main_file(main.clone())
+        .build();
+
+    let doc = engine
+        .compile::<PagedDocument>()
+        .output
+        .map_err(|e| match e {
+            TypstAsLibError::TypstSource(diags) => compile_error(&diags, &main, &layout),
+            other => CompileError(vec![Diagnostic::plain(
+                Level::Error,
+                file,
+                other.to_string(),
+            )]),
+        })?;
+    let pdf = typst_pdf::pdf(&doc, &PdfOptions::default())
+        .map_err(|diags| compile_error(&diags, &main, &layout))?;
+    Ok(pdf)
+}
+
+fn compile_error(diags: &[SourceDiagnostic], main: &Source, layout: &SourceLayout) -> CompileError {
+    CompileError(
+        diags
+            .iter()
+            .map(|d| Diagnostic::from_typst(d, main, layout))
+            .collect(),
+    )
 }
 
 fn postprocess_typst_html(raw: &str) -> String {
@@ -351,154 +811,40 @@ fn maybe_minify_html(html: String) -> String {
     html
 }
 
-fn load_binary_assets() -> Result<Vec<(String, Vec<u8>)>> {
-    use std::sync::Arc;
-    let mut bins = Vec::new();
-    let images_dir = PathBuf::from("static/images");
-    if images_dir.exists() {
-        for entry in fs::read_dir(&images_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() {
-                if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                    if matches!(
-                        ext.to_lowercase().as_str(),
-                        "png" | "jpg" | "jpeg" | "svg" | "gif" | "webp"
-                    ) {
-                        let bytes = Arc::new(fs::read(&path)?);
-                        let fname = path.file_name().unwrap().to_string_lossy().to_string();
-                        let variants = [
-                            fname.clone(),
-                            format!("/{fname}"),
-                            format!("/images/{fname}"),
-                            format!("static/images/{fname}"),
-                            format!("./static/images/{fname}"),
-                        ];
-                        for v in variants {
-                            bins.push((v, (*bytes).clone()));
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    let other_assets = vec![
-        (
-            "github-light.tmTheme".to_string(),
-            include_bytes!("../static/github-light.tmTheme").to_vec(),
-        ),
-        (
-            "github-dark.tmTheme".to_string(),
-            include_bytes!("../static/github-dark.tmTheme").to_vec(),
-        ),
-    ];
-
-    bins.extend(other_assets);
-
-    let gen_index = PathBuf::from("static/generated/index.json");
-    if gen_index.exists() {
-        if let Ok(bytes) = fs::read(&gen_index) {
-            let bytes = Arc::new(bytes);
-            let variants = [
-                "static/generated/index.json".to_string(),
-                "/static/generated/index.json".to_string(),
-                "./static/generated/index.json".to_string(),
-                "generated/index.json".to_string(),
-            ];
-            for v in variants {
-                bins.push((v, (*bytes).clone()));
-            }
-        }
-    }
-
-    Ok(bins)
+struct Preamble {
+    path: String,
+    text: String,
 }
 
-fn load_preamble(path: &str) -> String {
-    fs::read_to_string(path).unwrap_or_else(|_| {
+fn load_preamble(path: &str, site: &Site) -> Preamble {
+    let mut text = fs::read_to_string(path).unwrap_or_else(|_| {
         r#"// Global defaults; each article can override below.
 "#
         .to_string()
-    })
+    });
+    // プリアンブルの既定値を rodin.toml の値で定義し直す（末尾に足すので行番号はずれない）
+    let twitter = site
+        .twitter_url()
+        .map_or_else(|| "none".to_string(), |url| format!("{url:?}"));
+    text.push_str(&format!("\n#let twitter-link = {twitter}\n"));
+    Preamble {
+        path: path.to_string(),
+        text,
+    }
 }
 
+/// プリアンブルの import を消す（行番号がずれないよう空行にする）
 fn strip_preamble_import(source: &str) -> String {
     source
         .lines()
-        .filter(|line| {
+        .map(|line| {
             let l = line.trim_start();
-            !(l.starts_with("#import") && l.contains("preamble.typ"))
-        })
-        .collect::<Vec<_>>()
-        .join("\n")
-}
-
-fn build_cards_html(metas: &[FrontMatter]) -> String {
-    metas
-        .iter()
-        // sort by published_at desc
-        .sorted_by(|a, b| {
-            let a_time = a
-                .published_at
-                .as_deref()
-                .or(a.updated_at.as_deref())
-                .unwrap_or("");
-            let b_time = b
-                .published_at
-                .as_deref()
-                .or(b.updated_at.as_deref())
-                .unwrap_or("");
-            b_time.cmp(a_time)
-        })
-        .take(5)
-        .map(|post| {
-            let title = post.title.as_deref().unwrap_or(&post.slug);
-            let slug = &post.slug;
-            let date = post.published_at.as_deref().unwrap_or("");
-            let updated = post.updated_at.as_deref().unwrap_or(date);
-            let tags = &post.tags;
-            let description = post
-                .meta
-                .get("description")
-                .or_else(|| post.meta.get("og:description"))
-                .map(String::as_str)
-                .unwrap_or("");
-
-            let tags_html = if !tags.is_empty() {
-                let chips: String = tags
-                    .iter()
-                    .map(|t| format!("<span>#{}</span>", t))
-                    .collect::<Vec<_>>()
-                    .join("");
-                format!("<div class=\"flex flex-wrap gap-2 pt-1\">{chips}</div>")
-            } else {
-                String::new()
-            };
-
-            let updated_html = if !updated.is_empty() {
-                format!("Updated: {}", updated)
-            } else {
-                String::new()
-            };
-            let published_html = if !date.is_empty() {
-                format!("Published: {}", date)
+            if l.starts_with("#import") && l.contains("preamble.typ") {
+                ""
             } else {
-                String::new()
-            };
-
-            format!(
-                "<article class=\"post-card\">\
-                    <div>\
-                        <a href=\"/blog/{slug}\" class=\"not-prose\">{title}</a>\
-                        <div>{updated_html}</div>\
-                    </div>\
-                    <div>{description}</div>\
-                    <div>{published_html}</div>\
-                    {tags_html}\
-                </article>"
-            )
+                line
+            }
         })
         .collect::<Vec<_>>()
-        .join("")
+        .join("\n")
 }
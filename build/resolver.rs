@@ -0,0 +1,116 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use typst_as_lib::file_resolver::FileResolver;
+use typst_library::{
+    diag::{FileError, FileResult},
+    foundations::Bytes,
+    syntax::{FileId, Source},
+};
+
+/// Typst から `image("foo.png")` などで読むファイルの置き場所
+pub const ASSET_ROOT: &str = "static";
+/// ディレクトリを省いたパスはここから探す
+const IMAGES_DIR: &str = "images";
+/// ビルド中に書き出されるので読み込み結果を使い回さない
+const GENERATED_DIR: &str = "generated";
+
+/// static/ 以下のファイルを必要になったときに読む Typst のファイルリゾルバ
+///
+/// `foo.png`・`/images/foo.png`・`static/images/foo.png`・`./static/images/foo.png` は
+/// どれも static/images/foo.png を指す。`sub/foo.png` のようなサブディレクトリも読める。
+/// 中身は共有のキャッシュに持つので、clone して全部のコンパイルに渡してよい。
+#[derive(Clone)]
+pub struct AssetResolver {
+    root: Arc<PathBuf>,
+    cache: Arc<Mutex<HashMap<PathBuf, Bytes>>>,
+}
+
+impl AssetResolver {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: Arc::new(root.into()),
+            cache: Arc::default(),
+        }
+    }
+
+    /// 記事中の文字列をアセットとして読む（見つからなければ None）
+    pub fn read(&self, path: &str) -> Option<Bytes> {
+        self.load(Path::new(path)).ok()
+    }
+
+    fn load(&self, path: &Path) -> FileResult<Bytes> {
+        let Some(rel) = normalize(path) else {
+            return Err(FileError::NotFound(path.to_path_buf()));
+        };
+        if let Some(bytes) = self.cache.lock().ok().and_then(|c| c.get(&rel).cloned()) {
+            return Ok(bytes);
+        }
+        let Some(found) = self.candidates(&rel).into_iter().find(|p| p.is_file()) else {
+            return Err(FileError::Other(Some(
+                format!(
+                    "asset `{}` not found (looked in {})",
+                    path.display(),
+                    self.candidates(&rel)
+                        .iter()
+                        .map(|p| p.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+                .into(),
+            )));
+        };
+        let bytes = Bytes::new(fs::read(&found).map_err(|e| FileError::from_io(e, &found))?);
+        if !rel.starts_with(GENERATED_DIR) {
+            if let Ok(mut cache) = self.cache.lock() {
+                cache.insert(rel, bytes.clone());
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// `images/foo.png` → static/images/foo.png、`foo.png` → static/foo.png か static/images/foo.png
+    fn candidates(&self, rel: &Path) -> Vec<PathBuf> {
+        let rel = rel.strip_prefix(ASSET_ROOT).unwrap_or(rel);
+        let mut out = vec![self.root.join(rel)];
+        if !rel.starts_with(IMAGES_DIR) {
+            out.push(self.root.join(IMAGES_DIR).join(rel));
+        }
+        out
+    }
+}
+
+impl FileResolver for AssetResolver {
+    fn resolve_binary(&self, id: FileId) -> FileResult<Cow<'_, Bytes>> {
+        // パッケージの中身は typst-as-lib 側に任せる
+        if id.package().is_some() {
+            return Err(FileError::NotFound(id.vpath().as_rootless_path().into()));
+        }
+        self.load(id.vpath().as_rootless_path()).map(Cow::Owned)
+    }
+
+    fn resolve_source(&self, id: FileId) -> FileResult<Cow<'_, Source>> {
+        Err(FileError::NotFound(id.vpath().as_rootless_path().into()))
+    }
+}
+
+/// `./`・先頭の `/`・`a/../b` を畳んで相対パスにする（ルートより上に出るものは None）
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::ParentDir => {
+                if !out.pop() {
+                    return None;
+                }
+            }
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+    (!out.as_os_str().is_empty()).then_some(out)
+}
@@ -0,0 +1,368 @@
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use image::{
+    codecs::{avif::AvifEncoder, jpeg::JpegEncoder, png::PngEncoder},
+    imageops::FilterType,
+    DynamicImage, GenericImageView,
+};
+use rayon::prelude::*;
+use regex::{Captures, Regex};
+use std::{
+    collections::HashSet,
+    fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
+};
+
+use crate::{
+    cache,
+    images::{ImageManifest, ImageVariant, ResponsiveImage, IMAGE_MANIFEST_PATH},
+};
+
+/// サイト共通の画像（/assets/images/... で配信）
+pub const SITE_IMAGES_DIR: &str = "static/images";
+const SITE_IMAGES_URL: &str = "/assets/images";
+const OUT_DIR: &str = "static/generated/images";
+const OUT_URL: &str = "/assets/generated/images";
+/// 元画像より大きいものは作らない（元の幅は常に入れる）
+const WIDTHS: &[u32] = &[480, 960, 1440, 1920, 2560];
+const LQIP_WIDTH: u32 = 24;
+const AVIF_QUALITY: f32 = 60.0;
+const AVIF_SPEED: u8 = 8;
+const WEBP_QUALITY: f32 = 75.0;
+const JPEG_QUALITY: u8 = 80;
+/// 記事本文の画像の表示幅（本文カラムの最大幅に合わせる）
+const CONTENT_SIZES: &str = "(max-width: 768px) 100vw, 768px";
+
+static IMG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<img\b([^>]*?)/?>").expect("valid regex"));
+static ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"([a-zA-Z_:][-a-zA-Z0-9_:.]*)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+)))?"#)
+        .expect("valid regex")
+});
+
+/// static/images と記事バンドルの画像から AVIF / WebP / JPEG の縮小版と LQIP を作る
+///
+/// 元画像のハッシュが前回と同じなら作り直さない。使われなくなった生成物は `save` で消す。
+pub struct ImagePipeline {
+    previous: ImageManifest,
+    manifest: Mutex<ImageManifest>,
+}
+
+impl ImagePipeline {
+    pub fn load() -> Self {
+        let previous = fs::read(IMAGE_MANIFEST_PATH)
+            .ok()
+            .and_then(|b| serde_json::from_slice(&b).ok())
+            .unwrap_or_default();
+        Self {
+            previous,
+            manifest: Mutex::new(ImageManifest::new()),
+        }
+    }
+
+    /// static/images 以下を全部処理する
+    pub fn process_site_images(&self) -> Result<()> {
+        self.process_dir(Path::new(SITE_IMAGES_DIR), SITE_IMAGES_URL)
+    }
+
+    /// `dir` 以下の画像を処理する。`url_prefix` は `dir` を配信している URL
+    pub fn process_dir(&self, dir: &Path, url_prefix: &str) -> Result<()> {
+        if !dir.exists() {
+            return Ok(());
+        }
+        let mut files = Vec::new();
+        let mut stack = vec![dir.to_path_buf()];
+        while let Some(current) = stack.pop() {
+            for entry in fs::read_dir(&current)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    stack.push(path);
+                } else if is_resizable(&path) {
+                    files.push(path);
+                }
+            }
+        }
+        files.par_iter().try_for_each(|path| {
+            let rel = path
+                .strip_prefix(dir)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let url = format!("{url_prefix}/{rel}");
+            self.process_file(path, &url)
+                .with_context(|| format!("failed to process image {}", path.display()))
+        })
+    }
+
+    fn process_file(&self, path: &Path, url: &str) -> Result<()> {
+        let bytes = fs::read(path)?;
+        let hash = cache::hash_bytes(&bytes);
+        let reusable = self
+            .previous
+            .get(url)
+            .filter(|prev| prev.hash == hash && variants_exist(prev))
+            .cloned();
+        let entry = match reusable {
+            Some(prev) => prev,
+            None => generate(&bytes, url, &hash)?,
+        };
+        self.manifest
+            .lock()
+            .map_err(|_| anyhow!("image manifest lock poisoned"))?
+            .insert(url.to_string(), entry);
+        Ok(())
+    }
+
+    /// 本文の `<img>` のうち処理済みの画像を `<picture>` に置き換える
+    pub fn rewrite_html(&self, html: &str) -> String {
+        let Ok(manifest) = self.manifest.lock() else {
+            return html.to_string();
+        };
+        IMG_RE
+            .replace_all(html, |caps: &Captures| {
+                let attrs = parse_attrs(&caps[1]);
+                let image = attrs
+                    .iter()
+                    .find(|(k, _)| k == "src")
+                    .and_then(|(_, src)| manifest.get(&normalize_src(src)));
+                match image {
+                    Some(image) => picture_html(image, &attrs),
+                    None => caps[0].to_string(),
+                }
+            })
+            .into_owned()
+    }
+
+    /// マニフェストを書き、どの画像からも参照されていない生成物を消す
+    pub fn save(self) -> Result<()> {
+        let manifest = self
+            .manifest
+            .into_inner()
+            .map_err(|_| anyhow!("image manifest lock poisoned"))?;
+        let keep: HashSet<String> = manifest
+            .values()
+            .flat_map(|img| img.avif.iter().chain(&img.webp).chain(&img.fallback))
+            .filter_map(|v| v.url.rsplit('/').next().map(str::to_string))
+            .collect();
+        fs::create_dir_all(OUT_DIR)?;
+        for entry in fs::read_dir(OUT_DIR)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.path() != Path::new(IMAGE_MANIFEST_PATH) && !keep.contains(&name) {
+                fs::remove_file(entry.path())?;
+            }
+        }
+        fs::write(
+            IMAGE_MANIFEST_PATH,
+            serde_json::to_string_pretty(&manifest)?,
+        )?;
+        Ok(())
+    }
+}
+
+fn is_resizable(path: &Path) -> bool {
+    // GIF はアニメーションが消えるので、SVG はそもそも縮小しないので対象外
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    matches!(ext.as_deref(), Some("png" | "jpg" | "jpeg" | "webp"))
+}
+
+fn variants_exist(image: &ResponsiveImage) -> bool {
+    image
+        .avif
+        .iter()
+        .chain(&image.webp)
+        .chain(&image.fallback)
+        .all(|v| output_path(&v.url).exists())
+}
+
+fn output_path(url: &str) -> PathBuf {
+    let name = url.rsplit('/').next().unwrap_or_default();
+    PathBuf::from(OUT_DIR).join(name)
+}
+
+fn generate(bytes: &[u8], url: &str, hash: &str) -> Result<ResponsiveImage> {
+    let img = image::load_from_memory(bytes)?;
+    let (width, height) = img.dimensions();
+    let has_alpha = img.color().has_alpha();
+    // 同じ名前の画像が別の場所にあってもぶつからないよう、名前にハッシュを入れる
+    let stem = url
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.').map(|(s, _)| s))
+        .unwrap_or("image");
+    let base = format!("{stem}-{}", &hash[..12]);
+
+    let mut widths: Vec<u32> = WIDTHS.iter().copied().filter(|w| *w < width).collect();
+    widths.push(width.min(*WIDTHS.last().unwrap_or(&width)));
+    widths.dedup();
+
+    let mut out = ResponsiveImage {
+        hash: hash.to_string(),
+        width,
+        height,
+        lqip: lqip(&img)?,
+        fallback_type: if has_alpha { "image/png" } else { "image/jpeg" }.to_string(),
+        ..Default::default()
+    };
+    for w in widths {
+        let resized = if w == width {
+            img.clone()
+        } else {
+            let h = (u64::from(height) * u64::from(w) / u64::from(width)).max(1) as u32;
+            img.resize_exact(w, h, FilterType::Lanczos3)
+        };
+        out.avif
+            .push(write_variant(&base, w, "avif", encode_avif(&resized)?)?);
+        out.webp
+            .push(write_variant(&base, w, "webp", encode_webp(&resized)?)?);
+        let fallback = if has_alpha {
+            write_variant(&base, w, "png", encode_png(&resized)?)?
+        } else {
+            write_variant(&base, w, "jpg", encode_jpeg(&resized, JPEG_QUALITY)?)?
+        };
+        out.fallback.push(fallback);
+    }
+    Ok(out)
+}
+
+fn write_variant(base: &str, width: u32, ext: &str, bytes: Vec<u8>) -> Result<ImageVariant> {
+    let name = format!("{base}-{width}.{ext}");
+    fs::create_dir_all(OUT_DIR)?;
+    fs::write(PathBuf::from(OUT_DIR).join(&name), bytes)?;
+    Ok(ImageVariant {
+        width,
+        url: format!("{OUT_URL}/{name}"),
+    })
+}
+
+fn encode_avif(img: &DynamicImage) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    let encoder = AvifEncoder::new_with_speed_quality(&mut buf, AVIF_SPEED, AVIF_QUALITY as u8);
+    img.write_with_encoder(encoder)?;
+    Ok(buf)
+}
+
+fn encode_webp(img: &DynamicImage) -> Result<Vec<u8>> {
+    let (w, h) = img.dimensions();
+    let encoded = if img.color().has_alpha() {
+        webp::Encoder::from_rgba(img.to_rgba8().as_raw(), w, h).encode(WEBP_QUALITY)
+    } else {
+        webp::Encoder::from_rgb(img.to_rgb8().as_raw(), w, h).encode(WEBP_QUALITY)
+    };
+    Ok(encoded.to_vec())
+}
+
+fn encode_jpeg(img: &DynamicImage, quality: u8) -> Result<Vec<u8>> {
+    let mut buf = Cursor::new(Vec::new());
+    DynamicImage::ImageRgb8(img.to_rgb8())
+        .write_with_encoder(JpegEncoder::new_with_quality(&mut buf, quality))?;
+    Ok(buf.into_inner())
+}
+
+fn encode_png(img: &DynamicImage) -> Result<Vec<u8>> {
+    let mut buf = Cursor::new(Vec::new());
+    img.write_with_encoder(PngEncoder::new(&mut buf))?;
+    Ok(buf.into_inner())
+}
+
+/// 読み込み中に背景として敷くぼかし画像
+fn lqip(img: &DynamicImage) -> Result<String> {
+    let (w, h) = img.dimensions();
+    let lh = (u64::from(h) * u64::from(LQIP_WIDTH) / u64::from(w.max(1))).max(1) as u32;
+    let tiny = img
+        .resize_exact(LQIP_WIDTH, lh, FilterType::Triangle)
+        .blur(1.0);
+    let jpeg = encode_jpeg(&tiny, 40)?;
+    Ok(format!("data:image/jpeg;base64,{}", BASE64.encode(jpeg)))
+}
+
+fn parse_attrs(raw: &str) -> Vec<(String, String)> {
+    ATTR_RE
+        .captures_iter(raw)
+        .map(|c| {
+            let value = c
+                .get(2)
+                .or_else(|| c.get(3))
+                .or_else(|| c.get(4))
+                .map_or("", |m| m.as_str());
+            (c[1].to_ascii_lowercase(), value.to_string())
+        })
+        .collect()
+}
+
+/// Typst の `img("...")` に書かれがちな書き方を配信 URL にそろえる
+fn normalize_src(src: &str) -> String {
+    let src = src.trim().trim_start_matches("./");
+    for prefix in ["static/images/", "/images/", "images/"] {
+        if let Some(rest) = src.strip_prefix(prefix) {
+            return format!("{SITE_IMAGES_URL}/{rest}");
+        }
+    }
+    src.to_string()
+}
+
+fn picture_html(image: &ResponsiveImage, attrs: &[(String, String)]) -> String {
+    let get = |key: &str| {
+        attrs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    // 書き手が幅だけ指定したときは縦横比から高さを出す
+    let (width, height) = match get("width").and_then(|w| w.parse::<u32>().ok()) {
+        Some(w) => (
+            w,
+            (u64::from(image.height) * u64::from(w) / u64::from(image.width.max(1))) as u32,
+        ),
+        None => (image.width, image.height),
+    };
+    let sizes = get("sizes").unwrap_or(CONTENT_SIZES);
+    let passthrough: String = attrs
+        .iter()
+        .filter(|(k, _)| {
+            !matches!(
+                k.as_str(),
+                "src" | "srcset" | "sizes" | "width" | "height" | "loading" | "decoding" | "style"
+            )
+        })
+        .map(|(k, v)| format!(r#" {k}="{}""#, escape_attr(v)))
+        .collect();
+    let loading = get("loading").unwrap_or("lazy");
+    let style = format!(
+        "background:url({}) center/cover no-repeat;{}",
+        image.lqip,
+        get("style").unwrap_or_default()
+    );
+    let src = image.fallback_src().unwrap_or_default();
+    format!(
+        concat!(
+            r#"<picture>"#,
+            r#"<source type="image/avif" srcset="{avif}" sizes="{sizes}" />"#,
+            r#"<source type="image/webp" srcset="{webp}" sizes="{sizes}" />"#,
+            r#"<img src="{src}" srcset="{fallback}" sizes="{sizes}" width="{width}" height="{height}"{passthrough} loading="{loading}" decoding="async" style="{style}" />"#,
+            r#"</picture>"#
+        ),
+        avif = ResponsiveImage::srcset(&image.avif),
+        webp = ResponsiveImage::srcset(&image.webp),
+        fallback = ResponsiveImage::srcset(&image.fallback),
+        sizes = escape_attr(sizes),
+        style = escape_attr(&style),
+        src = src,
+        width = width,
+        height = height,
+        passthrough = passthrough,
+        loading = escape_attr(loading),
+    )
+}
+
+fn escape_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+}
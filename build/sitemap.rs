@@ -1,13 +1,13 @@
-use crate::frontmatter::FrontMatter;
+use crate::frontmatter::{series_path, tag_key, tag_path, FrontMatter};
 use anyhow::Result;
-use std::{fs, path::Path};
+use std::{collections::BTreeMap, fs, path::Path};
 
 pub fn write_sitemap(
     metas: &[FrontMatter],
     pgp_meta: Option<&FrontMatter>,
     site_url: &str,
     output_path: &str,
-) -> Result<()> {
+) -> Result<usize> {
     let homepage_lastmod = latest_lastmod(metas);
     let mut urls = Vec::with_capacity(metas.len() + 3);
     urls.push(SitemapEntry {
@@ -40,13 +40,33 @@ pub fn write_sitemap(
         urls.push(SitemapEntry { loc, lastmod });
     }
 
+    let tags = tag_lastmods(metas);
+    if !tags.is_empty() {
+        urls.push(SitemapEntry {
+            loc: format!("{site_url}/tags"),
+            lastmod: homepage_lastmod.clone(),
+        });
+        for (path, lastmod) in tags {
+            urls.push(SitemapEntry {
+                loc: format!("{site_url}{path}"),
+                lastmod,
+            });
+        }
+    }
+
+    for (path, lastmod) in series_lastmods(metas) {
+        urls.push(SitemapEntry {
+            loc: format!("{site_url}{path}"),
+            lastmod,
+        });
+    }
+
     let xml = render_xml(&urls);
     if let Some(dir) = Path::new(output_path).parent() {
         fs::create_dir_all(dir)?;
     }
     fs::write(output_path, xml)?;
-    println!("cargo:warning=generated sitemap with {} urls", urls.len());
-    Ok(())
+    Ok(urls.len())
 }
 
 fn latest_lastmod(metas: &[FrontMatter]) -> Option<String> {
@@ -57,6 +77,48 @@ fn latest_lastmod(metas: &[FrontMatter]) -> Option<String> {
         .max()
 }
 
+/// タグごとのパスと、そのタグが付いた記事の最新の更新日
+fn tag_lastmods(metas: &[FrontMatter]) -> BTreeMap<String, Option<String>> {
+    let mut tags: BTreeMap<String, Option<String>> = BTreeMap::new();
+    for meta in metas {
+        let lastmod = meta
+            .updated_at
+            .as_ref()
+            .or(meta.published_at.as_ref())
+            .map(|s| s.trim().to_string());
+        for tag in &meta.tags {
+            if tag_key(tag).is_empty() {
+                continue;
+            }
+            let entry = tags.entry(tag_path(tag)).or_default();
+            if lastmod > *entry {
+                *entry = lastmod.clone();
+            }
+        }
+    }
+    tags
+}
+
+/// シリーズごとのパスと、そのシリーズの記事の最新の更新日
+fn series_lastmods(metas: &[FrontMatter]) -> BTreeMap<String, Option<String>> {
+    let mut series: BTreeMap<String, Option<String>> = BTreeMap::new();
+    for meta in metas {
+        let Some(id) = meta.series.as_deref() else {
+            continue;
+        };
+        let lastmod = meta
+            .updated_at
+            .as_ref()
+            .or(meta.published_at.as_ref())
+            .map(|s| s.trim().to_string());
+        let entry = series.entry(series_path(id)).or_default();
+        if lastmod > *entry {
+            *entry = lastmod;
+        }
+    }
+    series
+}
+
 #[derive(Clone)]
 struct SitemapEntry {
     loc: String,
@@ -1,12 +1,14 @@
 mod handlers;
+mod live_reload;
 pub mod render;
+mod search;
 mod state;
 
 // Re-export for use in logging
 pub use handlers::get_client_ip;
 
 use axum::http::HeaderValue;
-use std::{convert::Infallible, env, sync::OnceLock};
+use std::{convert::Infallible, sync::OnceLock};
 
 use axum::routing::get_service;
 use axum::{
@@ -19,10 +21,12 @@ use tower::service_fn;
 use tower_http::compression::CompressionLayer;
 use tower_http::services::{ServeDir, ServeFile};
 
-use crate::logging;
+use crate::{config::SiteConfig, logging};
 
 const RODIN_MARKDOWN_ENABLED: &str = env!("RODIN_MARKDOWN_ENABLED");
 const GIT_HASH: &str = env!("GIT_HASH");
+/// build.rs が書き、予約投稿の公開時には state が書き直す
+pub(crate) const SITEMAP_PATH: &str = "static/generated/sitemap.xml";
 
 #[inline]
 pub(crate) fn markdown_enabled() -> bool {
@@ -42,22 +46,34 @@ const AI_CRAWLER_PATTERNS: &[&str] = &[
     "Amazonbot",
 ];
 
+static CONFIG: OnceLock<SiteConfig> = OnceLock::new();
+
+/// `rodin.toml` を読んで検証する（起動時に一度だけ。以降は `config()` で読む）
+pub fn init_config() -> anyhow::Result<()> {
+    let config = SiteConfig::load()?;
+    CONFIG.get_or_init(|| config);
+    Ok(())
+}
+
 #[inline]
-fn env_flag(key: &str, default: bool) -> bool {
-    env::var(key)
-        .map(|v| {
-            matches!(
-                v.as_str(),
-                "1" | "true" | "TRUE" | "on" | "ON" | "yes" | "YES"
-            )
-        })
-        .unwrap_or(default)
+pub(crate) fn config() -> &'static SiteConfig {
+    CONFIG
+        .get()
+        .expect("init_config runs before anything reads the config")
 }
 
 pub async fn run() -> anyhow::Result<()> {
     let app_state = state::build_shared_state().await?;
 
-    let compression_enabled = env_flag("COMPRESSION_ENABLED", true);
+    let server = &config().server;
+
+    // 予約投稿の生成物は公開時刻まで /assets でも返さない
+    let assets = Router::new()
+        .fallback_service(ServeDir::new("static"))
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            handlers::asset_guard,
+        ));
 
     let static_root = ServeDir::new("static/root").fallback(service_fn(|_req| async move {
         let res = handlers::not_found_response().await;
@@ -70,28 +86,41 @@ pub async fn run() -> anyhow::Result<()> {
         .route("/pgp", get(handlers::pgp_handler))
         .route("/blog", get(handlers::blog_list_handler))
         .route("/blog/{slug}", get(handlers::blog_handler))
+        .route("/blog/{slug}/{*path}", get(handlers::blog_asset_handler))
+        .route("/preview/{slug}", get(handlers::preview_handler))
+        .route(
+            "/preview/{slug}/{*path}",
+            get(handlers::preview_asset_handler),
+        )
+        .route("/series/{id}", get(handlers::series_handler))
+        .route("/tags", get(handlers::tags_handler))
+        .route("/tags/{tag}", get(handlers::tag_handler))
+        .route("/tags/{tag}/{feed}", get(handlers::tag_feed_handler))
         .route("/search", get(handlers::search_handler))
         .route("/__admin/reload", post(handlers::reload_handler))
-        .route_service(
-            "/sitemap.xml",
-            ServeFile::new("static/generated/sitemap.xml"),
-        )
-        .nest_service("/assets", ServeDir::new("static"))
+        .route_service("/sitemap.xml", ServeFile::new(SITEMAP_PATH))
+        .route("/feed.atom", get(handlers::feed_atom_handler))
+        .route("/feed.xml", get(handlers::feed_rss_handler))
+        .route("/feed.json", get(handlers::feed_json_handler))
+        .nest_service("/assets", assets)
         .fallback_service(get_service(static_root))
         .with_state(app_state);
 
-    if compression_enabled {
+    if live_reload::enabled() {
+        app = app.route(
+            live_reload::LIVE_RELOAD_PATH,
+            get(live_reload::live_reload_handler),
+        );
+        tracing::info!("Live reload enabled at {}", live_reload::LIVE_RELOAD_PATH);
+    }
+    if server.compression_enabled {
         app = app.layer(CompressionLayer::new());
     }
     app = app.layer(middleware::from_fn(handlers::security_middleware));
     app = app.layer(middleware::from_fn(cache_headers_middleware));
     app = app.layer(middleware::from_fn(logging::access_log_middleware));
 
-    let bind = env::var("BIND_ADDRESS").unwrap_or_else(|_| "0.0.0.0".to_string());
-    let port = env::var("PORT")
-        .ok()
-        .and_then(|p| p.parse().ok())
-        .unwrap_or(3000);
+    let (bind, port) = (&server.bind_address, server.port);
     let listener = TcpListener::bind(format!("{}:{}", bind, port)).await?;
     tracing::info!("Server running on http://{}:{}", bind, port);
 
@@ -119,15 +148,14 @@ async fn shutdown_signal() {
     {
         let _ = tokio::signal::ctrl_c().await;
     }
+    live_reload::shutdown();
 }
 
 async fn cache_headers_middleware(
     req: axum::http::Request<axum::body::Body>,
     next: middleware::Next,
 ) -> axum::http::Response<axum::body::Body> {
-    static CACHE_ENABLED: OnceLock<bool> = OnceLock::new();
-    let cache_enabled = *CACHE_ENABLED.get_or_init(|| env_flag("CACHE_ENABLED", false));
-    if !cache_enabled {
+    if !config().server.cache_enabled {
         return next.run(req).await;
     }
 
@@ -147,6 +175,15 @@ async fn cache_headers_middleware(
 
     let mut res = next.run(req).await;
 
+    // 下書きのプレビュー（ページもアセットも）は共有キャッシュに載せない
+    if path_owned.starts_with("/preview/") {
+        res.headers_mut().insert(
+            axum::http::header::CACHE_CONTROL,
+            HeaderValue::from_static("private, no-store"),
+        );
+        return res;
+    }
+
     let is_asset = path_owned.starts_with("/assets/")
         || ext
             .map(|e| {
@@ -1,32 +1,64 @@
 use axum::{
     body::Body,
     extract::{ConnectInfo, Extension, Path, Query, State},
-    http::{HeaderMap, HeaderValue, Request, StatusCode},
+    http::{HeaderMap, HeaderValue, Request, StatusCode, Uri},
     middleware::Next,
     response::{Html, IntoResponse, Redirect, Response},
 };
+use chrono::{Offset, Utc};
+use percent_encoding::percent_decode_str;
 use std::{
+    collections::{HashMap, HashSet},
     env,
     net::SocketAddr,
     sync::{LazyLock, OnceLock},
 };
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
 
 use super::{
-    markdown_enabled,
+    config, markdown_enabled,
     render::inject_runtime_tokens,
+    search,
     state::{self, AppState, SharedAppState},
 };
-use crate::app::render::{render_search_page, SearchHit};
-
+use crate::app::render::{
+    render_search_page, HitSection, SearchFilterChip, SearchHit, SearchResults, TagSummary,
+};
+use crate::frontmatter::{is_valid_slug, tag_key, tag_path, PRIVATE_DIR};
+use crate::preview::preview_token;
+
+const BUNDLE_ASSETS_DIR: &str = "static/generated/posts";
+/// 下書きのバンドルのアセット（`PRIVATE_DIR` の下）
+const DRAFT_BUNDLE_ASSETS_DIR: &str = "posts";
+/// プレビューのページが `Path=/preview/{slug}` で置くトークン
+const PREVIEW_COOKIE: &str = "rodin_preview";
+/// サーバーとビルドだけが読む一覧（限定公開・予約投稿の記事も載っている）
+const INTERNAL_ASSETS: &[&str] = &[
+    "generated/index.json",
+    "generated/build-cache.json",
+    "generated/images/manifest.json",
+];
 const CSP_PREFIX: &str = "default-src 'self'; script-src 'self' 'nonce-";
-const CSP_SUFFIX: &str = "' static.cloudflareinsights.com platform.twitter.com 'strict-dynamic'; style-src 'self' 'unsafe-inline'; img-src 'self' data: blob:; font-src 'self'; connect-src 'self' cloudflareinsights.com; object-src 'none'; frame-src https://platform.twitter.com https://syndication.twitter.com; frame-ancestors 'self'; base-uri 'none'; form-action 'self'; trusted-types default rodin-spa rodin-twitter; require-trusted-types-for 'script'";
-
-pub(crate) static TRUST_PROXY_ENABLED: LazyLock<bool> = LazyLock::new(|| {
-    env::var("TRUST_PROXY")
-        .map(|v| v == "true")
-        .unwrap_or(false)
+/// nonce の後ろ（rodin.toml の [csp] で許可したオリジンを入れる）
+static CSP_SUFFIX: LazyLock<String> = LazyLock::new(|| {
+    let csp = &config().csp;
+    let sources = |list: &[String], empty: &str| {
+        if list.is_empty() {
+            empty.to_string()
+        } else {
+            list.iter().map(|s| format!(" {s}")).collect()
+        }
+    };
+    format!(
+        "'{} 'strict-dynamic'; style-src 'self' 'unsafe-inline'; img-src 'self' data: blob:; font-src 'self'; connect-src 'self'{}; object-src 'none'; frame-src{}; frame-ancestors 'self'; base-uri 'none'; form-action 'self'; trusted-types default rodin-spa rodin-twitter; require-trusted-types-for 'script'",
+        sources(&csp.script_src, ""),
+        sources(&csp.connect_src, ""),
+        sources(&csp.frame_src, " 'none'"),
+    )
 });
 static RELOAD_TOKEN: OnceLock<Option<String>> = OnceLock::new();
+static PREVIEW_SECRET: OnceLock<Option<String>> = OnceLock::new();
 
 fn reload_token() -> Option<&'static str> {
     RELOAD_TOKEN
@@ -34,6 +66,12 @@ fn reload_token() -> Option<&'static str> {
         .as_deref()
 }
 
+fn preview_secret() -> Option<&'static str> {
+    PREVIEW_SECRET
+        .get_or_init(|| env::var("PREVIEW_SECRET").ok().filter(|s| !s.is_empty()))
+        .as_deref()
+}
+
 /// Extract client IP from headers (with proxy support) or fallback to socket address
 pub fn get_client_ip(headers: &HeaderMap, socket_addr: &SocketAddr) -> String {
     client_ip_from_headers(headers).unwrap_or_else(|| socket_addr.ip().to_string())
@@ -139,6 +177,14 @@ pub async fn blog_handler(
         return markdown_response(&state, stripped, &headers).await;
     }
 
+    // /blog/{slug}.pdf は印刷用の PDF 版
+    if let Some(stripped) = slug_clean.strip_suffix(".pdf") {
+        return pdf_response(&state, stripped).await;
+    }
+
+    if !is_valid_slug(&slug_clean) || !state.is_published(&slug_clean, Utc::now()) {
+        return not_found_response().await;
+    }
     let prerendered = match state.blog_pages.get(&slug_clean) {
         Some(p) => p,
         None => return not_found_response().await,
@@ -149,9 +195,235 @@ pub async fn blog_handler(
     Html(html).into_response()
 }
 
+/// バンドル記事（content/<slug>/index.typ）の画像やデータを /blog/{slug}/{path} で返す
+pub async fn blog_asset_handler(
+    State(state): State<SharedAppState>,
+    Path((slug, path)): Path<(String, String)>,
+    req: Request<Body>,
+) -> Response {
+    {
+        let state = state.read().await;
+        if !state.blog_pages.contains_key(&slug) || !state.is_published(&slug, Utc::now()) {
+            return not_found_response().await;
+        }
+    }
+    bundle_file_response(std::path::Path::new(BUNDLE_ASSETS_DIR), &slug, &path, req).await
+}
+
+/// 下書きのバンドルのアセットを /preview/{slug}/{path} で返す
+///
+/// 画像などはトークンを付けずに読まれるので、プレビューのページが置いた cookie でも確かめる。
+pub async fn preview_asset_handler(
+    State(state): State<SharedAppState>,
+    Path((slug, path)): Path<(String, String)>,
+    Query(params): Query<PreviewQuery>,
+    req: Request<Body>,
+) -> Response {
+    let Some(secret) = preview_secret() else {
+        return not_found_response().await;
+    };
+    let now = Utc::now().timestamp();
+    let authorized = params
+        .token
+        .as_deref()
+        .into_iter()
+        .chain(preview_cookies(req.headers()))
+        .any(|token| verify_preview_token(secret, &slug, token, now));
+    if !authorized {
+        return (StatusCode::UNAUTHORIZED, "valid preview token required").into_response();
+    }
+    if !state.read().await.preview_pages.contains_key(&slug) {
+        return not_found_response().await;
+    }
+    let root = std::path::Path::new(PRIVATE_DIR).join(DRAFT_BUNDLE_ASSETS_DIR);
+    let mut res = bundle_file_response(&root, &slug, &path, req).await;
+    res.headers_mut().insert(
+        "X-Robots-Tag",
+        HeaderValue::from_static("noindex, nofollow"),
+    );
+    res
+}
+
+/// ビルド時にコピーした `root/{slug}/` の中のファイルだけを返す
+async fn bundle_file_response(
+    root: &std::path::Path,
+    slug: &str,
+    path: &str,
+    req: Request<Body>,
+) -> Response {
+    let traversal = path.contains('\\')
+        || path
+            .split('/')
+            .any(|seg| seg.is_empty() || seg == "." || seg == "..");
+    if traversal {
+        return not_found_response().await;
+    }
+    let file = root.join(slug).join(path);
+    if !tokio::fs::metadata(&file)
+        .await
+        .map(|m| m.is_file())
+        .unwrap_or(false)
+    {
+        return not_found_response().await;
+    }
+    match ServeFile::new(file).oneshot(req).await {
+        Ok(res) => res.into_response(),
+        Err(never) => match never {},
+    }
+}
+
+/// /assets（static をそのまま配信）で、ビルド用の一覧と公開前の予約投稿の生成物を隠す
+///
+/// ServeDir と同じくパーセントエンコードを戻して `.` や空の区間を除いてから比べる。
+pub async fn asset_guard(
+    State(state): State<SharedAppState>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(path) = asset_rel_path(req.uri().path()) else {
+        return not_found_response().await;
+    };
+    if INTERNAL_ASSETS.contains(&path.as_str()) {
+        return not_found_response().await;
+    }
+    let hidden = {
+        let state = state.read().await;
+        let bundle_root = BUNDLE_ASSETS_DIR.trim_start_matches("static/");
+        let slug = state
+            .scheduled_files
+            .get(&path)
+            .map(String::as_str)
+            .or_else(|| {
+                path.strip_prefix(bundle_root)?
+                    .strip_prefix('/')?
+                    .split('/')
+                    .next()
+            });
+        slug.is_some_and(|slug| !state.is_published(slug, Utc::now()))
+    };
+    if hidden {
+        return not_found_response().await;
+    }
+    next.run(req).await
+}
+
+/// `/generated/./foo%2Ehtml` -> `generated/foo.html`（`..` を含むものは None）
+fn asset_rel_path(path: &str) -> Option<String> {
+    let decoded = percent_decode_str(path).decode_utf8().ok()?;
+    let mut parts = Vec::new();
+    for part in decoded.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => return None,
+            _ => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PreviewQuery {
+    token: Option<String>,
+}
+
+pub async fn preview_handler(
+    State(state): State<SharedAppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(slug): Path<String>,
+    uri: Uri,
+    headers: HeaderMap,
+    Extension(nonce): Extension<String>,
+    Query(params): Query<PreviewQuery>,
+) -> Response {
+    // PREVIEW_SECRET が無ければプレビュー自体を無効にする
+    let Some(secret) = preview_secret() else {
+        return not_found_response().await;
+    };
+    let authorized = params
+        .token
+        .as_deref()
+        .map(|token| verify_preview_token(secret, &slug, token, Utc::now().timestamp()))
+        .unwrap_or(false);
+    let mut res = if !authorized {
+        (StatusCode::UNAUTHORIZED, "valid preview token required").into_response()
+    } else {
+        let state = state.read().await;
+        // 下書き以外（予約投稿・限定公開含む）は通常のページをそのまま使う
+        match state
+            .preview_pages
+            .get(&slug)
+            .or_else(|| state.blog_pages.get(&slug))
+        {
+            Some(prerendered) => {
+                let client_ip =
+                    client_ip_from_headers(&headers).unwrap_or_else(|| addr.ip().to_string());
+                Html(inject_runtime_tokens(prerendered, &client_ip, &nonce)).into_response()
+            }
+            None => not_found_response().await,
+        }
+    };
+    // 本文の画像など（/preview/{slug}/...）はトークン無しで読まれるので cookie で渡す
+    if let Some(token) = params
+        .token
+        .as_deref()
+        .filter(|_| res.status().is_success())
+    {
+        if let Some(cookie) = preview_cookie(uri.path(), token) {
+            res.headers_mut()
+                .insert(axum::http::header::SET_COOKIE, cookie);
+        }
+    }
+    res.headers_mut().insert(
+        "X-Robots-Tag",
+        HeaderValue::from_static("noindex, nofollow"),
+    );
+    res
+}
+
+/// トークンの期限まで `path`（/preview/{slug}）の下にだけ送られる cookie
+fn preview_cookie(path: &str, token: &str) -> Option<HeaderValue> {
+    let expires = token.split_once('.')?.0.parse::<i64>().ok()?;
+    let max_age = expires - Utc::now().timestamp();
+    HeaderValue::from_str(&format!(
+        "{PREVIEW_COOKIE}={token}; Path={path}; Max-Age={max_age}; HttpOnly; Secure; SameSite=Strict"
+    ))
+    .ok()
+}
+
+/// `Cookie` ヘッダーにあるプレビューのトークン
+fn preview_cookies(headers: &HeaderMap) -> impl Iterator<Item = &str> {
+    headers
+        .get_all(axum::http::header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().strip_prefix(PREVIEW_COOKIE)?.strip_prefix('='))
+}
+
+fn verify_preview_token(secret: &str, slug: &str, token: &str, now: i64) -> bool {
+    let Some(expires) = token
+        .split_once('.')
+        .and_then(|(exp, _)| exp.parse::<i64>().ok())
+    else {
+        return false;
+    };
+    if expires < now {
+        return false;
+    }
+    let expected = preview_token(secret, slug, expires);
+    // タイミング攻撃対策で長さ以外は全バイト比較する
+    expected.len() == token.len()
+        && expected
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
 #[derive(Debug, serde::Deserialize)]
 pub struct SearchQuery {
     q: Option<String>,
+    page: Option<u32>,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -160,6 +432,51 @@ pub struct BlogListQuery {
 }
 
 const POSTS_PER_PAGE: usize = 10;
+const SEARCH_RESULTS_PER_PAGE: usize = 10;
+
+fn blog_list_item(e: &state::SearchIndexEntry) -> crate::app::render::BlogListItem {
+    crate::app::render::BlogListItem {
+        slug: e.slug.clone(),
+        title: e.title.clone(),
+        published_at: e.published_at.clone(),
+        updated_at: e.updated_at.clone(),
+        description: e.description.clone(),
+        tags: e.tags.clone(),
+    }
+}
+
+/// 連載の記事を順番に並べたページ
+pub async fn series_handler(
+    State(state): State<SharedAppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Extension(nonce): Extension<String>,
+) -> Response {
+    let state = state.read().await;
+    let client_ip = addr.ip().to_string();
+    let now = Utc::now();
+    let Some(slugs) = state.series.get(id.trim()) else {
+        return not_found_response().await;
+    };
+    let posts: Vec<_> = slugs
+        .iter()
+        .filter_map(|slug| {
+            state
+                .search_index
+                .entries()
+                .iter()
+                .find(|e| &e.slug == slug)
+        })
+        .filter(|e| e.is_listed_at(now))
+        .map(blog_list_item)
+        .collect();
+    if posts.is_empty() {
+        return not_found_response().await;
+    }
+
+    let html = crate::app::render::render_series_page(&client_ip, &nonce, id.trim(), posts);
+    Html(html).into_response()
+}
 
 pub async fn blog_list_handler(
     State(state): State<SharedAppState>,
@@ -172,17 +489,13 @@ pub async fn blog_list_handler(
     let page = params.page.unwrap_or(1).max(1) as usize;
 
     // 投稿を日付でソート（新しい順）
+    let now = Utc::now();
     let mut posts: Vec<_> = state
         .search_index
+        .entries()
         .iter()
-        .map(|e| crate::app::render::BlogListItem {
-            slug: e.slug.clone(),
-            title: e.title.clone(),
-            published_at: e.published_at.clone(),
-            updated_at: e.updated_at.clone(),
-            description: e.description.clone(),
-            tags: e.tags.clone(),
-        })
+        .filter(|e| e.is_listed_at(now))
+        .map(blog_list_item)
         .collect();
     posts.sort_by(|a, b| b.published_at.cmp(&a.published_at));
 
@@ -201,72 +514,252 @@ pub async fn blog_list_handler(
     Html(html).into_response()
 }
 
-pub async fn search_handler(
+pub async fn tags_handler(
     State(state): State<SharedAppState>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Extension(nonce): Extension<String>,
-    Query(params): Query<SearchQuery>,
 ) -> Response {
     let state = state.read().await;
     let client_ip = addr.ip().to_string();
-    let q_raw = params.q.unwrap_or_default();
-    let q = q_raw.trim();
-    let mut hits = Vec::new();
-    if !q.is_empty() {
-        let q_lc = q.to_lowercase();
-        let q_chars: Vec<char> = q_lc.chars().collect();
-        for entry in state.search_index.iter() {
-            if entry.title_lc.contains(&q_lc) || entry.body_lc.contains(&q_lc) {
-                let snippet = build_snippet(&entry.body_chars, &entry.body_lower, &q_chars);
-                hits.push(SearchHit {
-                    title: entry.title.clone(),
-                    slug: entry.slug.clone(),
-                    snippet,
-                    published_at: entry.published_at.clone(),
-                    updated_at: entry.updated_at.clone(),
-                });
-                if hits.len() >= 30 {
-                    break;
+
+    // 表記揺れはキーでまとめ、最初に出てきた表記を表示に使う
+    let mut tags: Vec<TagSummary> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let now = Utc::now();
+    for entry in state
+        .search_index
+        .entries()
+        .iter()
+        .filter(|e| e.is_listed_at(now))
+    {
+        // 同じ記事に "Rust" と "rust" があっても 1 件として数える
+        let mut counted = HashSet::new();
+        for tag in &entry.tags {
+            let key = tag_key(tag);
+            if key.is_empty() || !counted.insert(key.clone()) {
+                continue;
+            }
+            match positions.get(&key) {
+                Some(&idx) => tags[idx].count += 1,
+                None => {
+                    positions.insert(key, tags.len());
+                    tags.push(TagSummary {
+                        name: tag.trim().to_string(),
+                        count: 1,
+                    });
                 }
             }
         }
     }
+    tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
 
-    let html = render_search_page(q.to_string(), &hits, &client_ip, &nonce);
-    let mut res = Html(html).into_response();
-    res.headers_mut().insert(
-        "X-Robots-Tag",
-        HeaderValue::from_static("noindex, nofollow"),
-    );
-    res
+    let html = crate::app::render::render_tags_page(&client_ip, &nonce, tags);
+    Html(html).into_response()
 }
 
-fn build_snippet(body_chars: &[char], body_lower: &[char], needle: &[char]) -> String {
-    let hit = find_subsequence(body_lower, needle);
-    let (start, end) = if let Some(pos) = hit {
-        let start = pos.saturating_sub(40);
-        let end = (pos + needle.len() + 120).min(body_chars.len());
-        (start, end)
-    } else {
-        (0, body_chars.len().min(160))
+pub async fn tag_handler(
+    State(state): State<SharedAppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(tag): Path<String>,
+    Extension(nonce): Extension<String>,
+    Query(params): Query<BlogListQuery>,
+) -> Response {
+    let state = state.read().await;
+    let client_ip = addr.ip().to_string();
+    let page = params.page.unwrap_or(1).max(1) as usize;
+    let key = tag_key(&tag);
+    let now = Utc::now();
+
+    let mut display = None;
+    let mut posts: Vec<_> = state
+        .search_index
+        .entries()
+        .iter()
+        .filter(|e| e.is_listed_at(now))
+        .filter(|e| match e.tags.iter().find(|t| tag_key(t) == key) {
+            Some(t) => {
+                display.get_or_insert_with(|| t.trim().to_string());
+                true
+            }
+            None => false,
+        })
+        .map(blog_list_item)
+        .collect();
+    let Some(display) = display else {
+        return not_found_response().await;
     };
+    posts.sort_by(|a, b| b.published_at.cmp(&a.published_at));
 
-    let mut snippet: String = body_chars[start..end].iter().collect();
-    if end < body_chars.len() {
-        snippet.push('…');
+    let total = posts.len();
+    let total_pages = total.div_ceil(POSTS_PER_PAGE);
+    if page > total_pages {
+        return not_found_response().await;
     }
-    snippet
+    let start = (page - 1) * POSTS_PER_PAGE;
+    let page_posts: Vec<_> = posts.into_iter().skip(start).take(POSTS_PER_PAGE).collect();
+
+    let html = crate::app::render::render_tag_page(
+        &client_ip,
+        &nonce,
+        &display,
+        page_posts,
+        page as u32,
+        total_pages as u32,
+    );
+    Html(html).into_response()
 }
 
-fn find_subsequence(haystack: &[char], needle: &[char]) -> Option<usize> {
-    if needle.is_empty() {
-        return None;
+#[derive(Clone, Copy)]
+enum FeedFormat {
+    Atom,
+    Rss,
+    Json,
+}
+
+impl FeedFormat {
+    fn from_file_name(name: &str) -> Option<Self> {
+        match name {
+            "feed.atom" => Some(Self::Atom),
+            "feed.xml" => Some(Self::Rss),
+            "feed.json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Atom => "atom",
+            Self::Rss => "xml",
+            Self::Json => "json",
+        }
     }
-    haystack.windows(needle.len()).position(|w| w == needle)
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Atom => "application/atom+xml; charset=utf-8",
+            Self::Rss => "application/rss+xml; charset=utf-8",
+            Self::Json => "application/feed+json; charset=utf-8",
+        }
+    }
+}
+
+async fn feed_file_response(path: std::path::PathBuf, format: FeedFormat) -> Response {
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => (
+            [(axum::http::header::CONTENT_TYPE, format.content_type())],
+            bytes,
+        )
+            .into_response(),
+        Err(_) => not_found_response().await,
+    }
+}
+
+pub async fn feed_atom_handler() -> Response {
+    feed_file_response("static/generated/feed.atom".into(), FeedFormat::Atom).await
+}
+
+pub async fn feed_rss_handler() -> Response {
+    feed_file_response("static/generated/feed.xml".into(), FeedFormat::Rss).await
+}
+
+pub async fn feed_json_handler() -> Response {
+    feed_file_response("static/generated/feed.json".into(), FeedFormat::Json).await
+}
+
+pub async fn tag_feed_handler(Path((tag, file)): Path<(String, String)>) -> Response {
+    let Some(format) = FeedFormat::from_file_name(&file) else {
+        return not_found_response().await;
+    };
+    // tag_path はパーセントエンコード済みなので '/' を含まない
+    let path = tag_path(&tag);
+    let stem = path.trim_start_matches("/tags/");
+    if stem.is_empty() {
+        return not_found_response().await;
+    }
+    let file = format!("static/generated/feeds/tags/{stem}.{}", format.extension());
+    feed_file_response(file.into(), format).await
+}
+
+pub async fn search_handler(
+    State(state): State<SharedAppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(nonce): Extension<String>,
+    Query(params): Query<SearchQuery>,
+) -> Response {
+    let state = state.read().await;
+    let client_ip = addr.ip().to_string();
+    let q_raw = params.q.unwrap_or_default();
+    let q = q_raw.trim();
+    let now = Utc::now();
+    // 日付の絞り込みはサイトのタイムゾーンで読む（起動時に検証済み）
+    let offset = config().site.timezone.parse().unwrap_or_else(|_| Utc.fix());
+    let parsed = if q.is_empty() {
+        None
+    } else {
+        Some(search::parse_query(q, offset))
+    };
+
+    let mut results = SearchResults::default();
+    match &parsed {
+        Some(Ok(query)) => {
+            // 絞り込みと順位付けは全件に対して行い、表示するページの分だけスニペットを作る
+            let ranked: Vec<_> = state
+                .search_index
+                .search(query, now)
+                .into_iter()
+                .filter(|r| r.entry.is_listed_at(now))
+                .collect();
+            results.total = ranked.len();
+            results.total_pages = results.total.div_ceil(SEARCH_RESULTS_PER_PAGE).max(1) as u32;
+            results.current_page = params.page.unwrap_or(1).clamp(1, results.total_pages);
+            let needles = query.needles();
+            results.hits = ranked
+                .into_iter()
+                .skip((results.current_page as usize - 1) * SEARCH_RESULTS_PER_PAGE)
+                .take(SEARCH_RESULTS_PER_PAGE)
+                .map(|r| {
+                    let snippet = search::snippet(r.entry, &needles);
+                    SearchHit {
+                        title: r.entry.title.clone(),
+                        slug: r.entry.slug.clone(),
+                        snippet_html: snippet.html,
+                        section: snippet.section.map(|s| HitSection {
+                            id: s.id.to_string(),
+                            title: s.title.to_string(),
+                        }),
+                        published_at: r.entry.published_at.clone(),
+                        updated_at: r.entry.updated_at.clone(),
+                        score: r.score,
+                    }
+                })
+                .collect();
+            results.filters = query
+                .filters
+                .iter()
+                .map(|f| SearchFilterChip {
+                    label: f.label.clone(),
+                    remaining_query: format!("{} {}", &q[..f.span.start], &q[f.span.end..])
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                })
+                .collect();
+        }
+        Some(Err(e)) => results.error = Some(e.to_string()),
+        None => {}
+    }
+
+    let html = render_search_page(q.to_string(), results, &client_ip, &nonce);
+    let mut res = Html(html).into_response();
+    res.headers_mut().insert(
+        "X-Robots-Tag",
+        HeaderValue::from_static("noindex, nofollow"),
+    );
+    res
 }
 
 fn client_ip_from_headers(headers: &HeaderMap) -> Option<String> {
-    if !*TRUST_PROXY_ENABLED {
+    if !config().server.trust_proxy {
         return None;
     }
     if let Some(val) = headers.get("CF-Connecting-IP") {
@@ -324,7 +817,7 @@ pub async fn pgp_handler(
 
 pub async fn raw_typ_response(state: &AppState, slug: &str, headers: &HeaderMap) -> Response {
     // 悪意駆動型人生を送っている人を防ぐ
-    if slug.contains('/') || slug.starts_with('_') {
+    if slug.contains('/') || slug.starts_with('_') || !state.is_published(slug, Utc::now()) {
         return not_found_response().await;
     }
     match state.blog_typs.get(slug) {
@@ -359,7 +852,7 @@ pub async fn markdown_response(state: &AppState, slug: &str, headers: &HeaderMap
         )
             .into_response();
     }
-    if slug.contains('/') || slug.starts_with('_') {
+    if slug.contains('/') || slug.starts_with('_') || !state.is_published(slug, Utc::now()) {
         return not_found_response().await;
     }
     match state.blog_markdowns.get(slug) {
@@ -380,6 +873,32 @@ pub async fn markdown_response(state: &AppState, slug: &str, headers: &HeaderMap
     }
 }
 
+pub async fn pdf_response(state: &AppState, slug: &str) -> Response {
+    if !state.is_published(slug, Utc::now()) {
+        return not_found_response().await;
+    }
+    let Some(path) = state.blog_pdfs.get(slug) else {
+        return not_found_response().await;
+    };
+    match tokio::fs::read(path).await {
+        Ok(bytes) => (
+            [
+                (
+                    axum::http::header::CONTENT_TYPE,
+                    "application/pdf".to_string(),
+                ),
+                (
+                    axum::http::header::CONTENT_DISPOSITION,
+                    format!("inline; filename=\"{slug}.pdf\""),
+                ),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Err(_) => not_found_response().await,
+    }
+}
+
 pub async fn not_found_response() -> Response {
     let html = r#"<!doctype html>
 <html lang="ja">
@@ -437,7 +956,7 @@ pub async fn security_middleware(mut req: Request<Body>, next: Next) -> Response
     let mut csp = String::with_capacity(CSP_PREFIX.len() + nonce.len() + CSP_SUFFIX.len());
     csp.push_str(CSP_PREFIX);
     csp.push_str(&nonce);
-    csp.push_str(CSP_SUFFIX);
+    csp.push_str(&CSP_SUFFIX);
     if let Ok(val) = HeaderValue::from_str(&csp) {
         res_headers.insert(axum::http::header::CONTENT_SECURITY_POLICY, val);
     }
@@ -450,10 +969,11 @@ pub async fn security_middleware(mut req: Request<Body>, next: Next) -> Response
         "Cross-Origin-Opener-Policy",
         HeaderValue::from_static("same-origin"),
     );
-    res_headers.insert(
-        "For-Inspectors",
-        HeaderValue::from_static("Follow https://x.com/suzuneu_discord please!"),
-    );
+    if let Some(url) = config().site.twitter_url() {
+        if let Ok(val) = HeaderValue::from_str(&format!("Follow {url} please!")) {
+            res_headers.insert("For-Inspectors", val);
+        }
+    }
     res_headers.insert("For-Scrapers", HeaderValue::from_static("You can use /blog/[slug].typ to get the raw Typst source. Please be kind to the server!"));
 
     // .typ や .md の場合 noindex
@@ -0,0 +1,104 @@
+use std::{convert::Infallible, sync::LazyLock};
+
+use axum::response::{
+    sse::{Event, KeepAlive, Sse},
+    IntoResponse, Response,
+};
+use futures::{stream, Stream};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use super::{config, render::CSP_NONCE_TOKEN};
+use crate::logging::Environment;
+
+/// ブラウザが購読する SSE のパス
+pub(crate) const LIVE_RELOAD_PATH: &str = "/__dev/live-reload";
+
+/// 開発環境（RODIN_ENV が prod 以外）で `server.live_reload` を有効にしたときだけ
+static ENABLED: LazyLock<bool> =
+    LazyLock::new(|| config().server.live_reload && Environment::from_env() == Environment::Dev);
+
+static EVENTS: LazyLock<broadcast::Sender<LiveEvent>> = LazyLock::new(|| broadcast::channel(16).0);
+
+#[derive(Clone)]
+enum LiveEvent {
+    Reloaded,
+    Failed(String),
+    /// graceful shutdown が開きっぱなしの接続を待たないように閉じる
+    Shutdown,
+}
+
+#[inline]
+pub(crate) fn enabled() -> bool {
+    *ENABLED
+}
+
+/// `state::reload_state` の結果を開いているタブに知らせる
+pub(crate) fn publish(result: &anyhow::Result<()>) {
+    if !enabled() {
+        return;
+    }
+    let event = match result {
+        Ok(()) => LiveEvent::Reloaded,
+        Err(e) => LiveEvent::Failed(format!("{e:#}")),
+    };
+    // 誰も購読していないときの Err は無視してよい
+    let _ = EVENTS.send(event);
+}
+
+pub(crate) fn shutdown() {
+    if enabled() {
+        let _ = EVENTS.send(LiveEvent::Shutdown);
+    }
+}
+
+pub async fn live_reload_handler() -> Response {
+    Sse::new(events())
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+fn events() -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(EVENTS.subscribe(), |mut rx| async move {
+        let event = match rx.recv().await {
+            Ok(LiveEvent::Reloaded) => Event::default().event("reloaded").data("ok"),
+            Ok(LiveEvent::Failed(message)) => Event::default().event("failed").data(message),
+            Ok(LiveEvent::Shutdown) | Err(RecvError::Closed) => return None,
+            // 取りこぼしても最新の状態で読み直せば同じ
+            Err(RecvError::Lagged(_)) => Event::default().event("reloaded").data("ok"),
+        };
+        Some((Ok(event), rx))
+    })
+}
+
+/// `wrap_html_with_options` が開発時だけ `<head>` に差し込むスクリプト
+///
+/// 再読み込みに成功したらページを読み直し、失敗したらエラーを重ねて表示する。
+/// サーバーを再起動したときも、つなぎ直せた時点で読み直す。
+pub(crate) fn client_snippet() -> String {
+    if !enabled() {
+        return String::new();
+    }
+    format!(
+        r#"<script nonce="{CSP_NONCE_TOKEN}">
+    (()=>{{
+      const es=new EventSource('{LIVE_RELOAD_PATH}');
+      let lost=false;
+      es.addEventListener('reloaded',()=>location.reload());
+      es.addEventListener('failed',e=>{{
+        let box=document.getElementById('rodin-live-reload-error');
+        if(!box){{
+          box=document.createElement('pre');
+          box.id='rodin-live-reload-error';
+          box.style.cssText='position:fixed;inset:0;z-index:2147483647;margin:0;padding:2rem;overflow:auto;white-space:pre-wrap;background:rgba(20,0,0,.92);color:#ffd6d6;font:14px/1.5 ui-monospace,monospace;cursor:pointer';
+          box.title='click to dismiss';
+          box.addEventListener('click',()=>box.remove());
+          document.body.appendChild(box);
+        }}
+        box.textContent='reload failed\n\n'+e.data;
+      }});
+      es.addEventListener('error',()=>{{lost=true;}});
+      es.addEventListener('open',()=>{{if(lost)location.reload();}});
+    }})();
+  </script>"#
+    )
+}
@@ -1,7 +1,9 @@
-use crate::asset::asset_url;
+use crate::app::{config, live_reload};
+use crate::asset::{asset_url, responsive_image};
 use crate::{
-    components::{BlogListPage, BlogPage, TopPage},
-    frontmatter::FrontMatter,
+    components::{BlogListPage, BlogPage, TagsPage, TopPage, HERO_IMAGE_SIZES, HERO_IMAGE_URL},
+    frontmatter::{series_path, tag_path, FrontMatter},
+    images::ResponsiveImage,
 };
 use leptos::prelude::*;
 use serde_json::{json, Map, Value};
@@ -12,16 +14,45 @@ use minify_html::{minify, Cfg as HtmlMinCfg};
 
 pub(crate) const CLIENT_IP_TOKEN: &str = "__CLIENT_IP_PLACEHOLDER__";
 pub(crate) const CSP_NONCE_TOKEN: &str = "__CSP_NONCE__";
-const SITE_URL: &str = "https://suzuneu.com";
-const ORG_ID: &str = "https://suzuneu.com/#organization";
 
 #[derive(Clone)]
 pub struct SearchHit {
     pub title: String,
     pub slug: String,
-    pub snippet: String,
+    /// 一致した語を `<mark>` で囲んだ抜粋（エスケープ済みの HTML）
+    pub snippet_html: String,
+    /// 抜粋を取った見出し
+    pub section: Option<HitSection>,
     pub published_at: Option<String>,
     pub updated_at: Option<String>,
+    /// 順位付けのスコア（ページには `data-score` として出す）
+    pub score: f32,
+}
+
+/// 記事の中の見出し（`/blog/{slug}#{id}` で飛べる）
+#[derive(Clone)]
+pub struct HitSection {
+    pub id: String,
+    pub title: String,
+}
+
+/// 検索式の絞り込み 1 つ（外したときの式つき）
+#[derive(Clone)]
+pub struct SearchFilterChip {
+    pub label: String,
+    pub remaining_query: String,
+}
+
+#[derive(Default)]
+pub struct SearchResults {
+    /// 表示するページの分だけ
+    pub hits: Vec<SearchHit>,
+    pub total: usize,
+    pub current_page: u32,
+    pub total_pages: u32,
+    pub filters: Vec<SearchFilterChip>,
+    /// 検索式が読めなかったときの説明
+    pub error: Option<String>,
 }
 
 #[derive(Clone)]
@@ -34,6 +65,55 @@ pub struct BlogListItem {
     pub tags: Vec<String>,
 }
 
+impl From<&FrontMatter> for BlogListItem {
+    fn from(meta: &FrontMatter) -> Self {
+        Self {
+            slug: meta.slug.clone(),
+            title: meta.title.clone().unwrap_or_else(|| "Untitled".to_string()),
+            published_at: meta.published_at.clone(),
+            updated_at: meta.updated_at.clone(),
+            description: meta
+                .meta
+                .get("description")
+                .or_else(|| meta.meta.get("og:description"))
+                .cloned(),
+            tags: meta.tags.clone(),
+        }
+    }
+}
+
+/// 記事の下に出す前後の記事と関連記事
+#[derive(Clone)]
+pub struct PostNav {
+    /// ひとつ古い記事
+    pub prev: Option<BlogListItem>,
+    /// ひとつ新しい記事
+    pub next: Option<BlogListItem>,
+    pub related: Vec<BlogListItem>,
+}
+
+#[derive(Clone)]
+pub struct TagSummary {
+    pub name: String,
+    pub count: usize,
+}
+
+#[derive(Clone)]
+pub struct SeriesLink {
+    pub slug: String,
+    pub title: String,
+}
+
+/// 記事ページに出す「全 M 回中 第 N 回」と前後の記事
+#[derive(Clone)]
+pub struct SeriesNav {
+    pub id: String,
+    pub position: usize,
+    pub total: usize,
+    pub prev: Option<SeriesLink>,
+    pub next: Option<SeriesLink>,
+}
+
 #[derive(Clone, Debug, Default)]
 pub(crate) struct HtmlOptions {
     pub meta: Option<HashMap<String, String>>,
@@ -42,6 +122,15 @@ pub(crate) struct HtmlOptions {
     pub head_scripts: Vec<String>,
 }
 
+/// Generate `<link rel="alternate">` tags for the Atom / RSS / JSON feeds under `base_path`
+pub(crate) fn feed_alternate_links(base_path: &str, title: &str) -> String {
+    format!(
+        r#"<link rel="alternate" type="application/atom+xml" title="{title} (Atom)" href="{base_path}/feed.atom" />
+  <link rel="alternate" type="application/rss+xml" title="{title} (RSS)" href="{base_path}/feed.xml" />
+  <link rel="alternate" type="application/feed+json" title="{title} (JSON Feed)" href="{base_path}/feed.json" />"#
+    )
+}
+
 /// Generate a lazy-loaded CSS link with noscript fallback
 pub(crate) fn lazy_css_link(href: &str) -> String {
     format!(
@@ -81,9 +170,13 @@ pub(crate) fn wrap_html_with_options(body: &str, title: &str, opts: &HtmlOptions
     } else {
         opts.head_scripts.join("\n  ")
     };
+    let site = &config().site;
+    let feed_links = feed_alternate_links("", &site.title);
+    let lang = site.lang();
+    let live_reload = live_reload::client_snippet();
     format!(
         r##"<!DOCTYPE html>
-<html lang="ja">
+<html lang="{lang}">
 <head>
   <meta charset="utf-8" />
   <link rel="preload" href="{font_preload_bold}" as="font" type="font/woff2" crossorigin />
@@ -103,6 +196,7 @@ pub(crate) fn wrap_html_with_options(body: &str, title: &str, opts: &HtmlOptions
   <link rel="apple-touch-icon" href="/apple-touch-icon.png" />
   <link rel="icon" href="/android-chrome-192x192.png" sizes="192x192" />
   <link rel="icon" href="/android-chrome-512x512.png" sizes="512x512" />
+  {feed_links}
   <link rel="stylesheet" href="{critical}" />
   <link rel="stylesheet" href="{lazy_css}" data-unblock-css="1" media="print" />
   <noscript><link rel="stylesheet" href="{lazy_css}" /></noscript>
@@ -117,6 +211,7 @@ pub(crate) fn wrap_html_with_options(body: &str, title: &str, opts: &HtmlOptions
   </script>
   <script type="module" src="{app_js}" nonce="{CSP_NONCE_TOKEN}" defer></script>
   {head_scripts}
+  {live_reload}
 </head>
 <body>
 {body}
@@ -126,8 +221,9 @@ pub(crate) fn wrap_html_with_options(body: &str, title: &str, opts: &HtmlOptions
 }
 
 pub(crate) fn prerender_top_page(home_html: &str) -> String {
+    let hero = responsive_image(HERO_IMAGE_URL);
     let rendered = Owner::new_root(None).with(|| {
-        view! { <TopPage client_ip=CLIENT_IP_TOKEN.to_string() home_html=home_html.to_string() current_path="/".to_string() /> }.to_html()
+        view! { <TopPage client_ip=CLIENT_IP_TOKEN.to_string() home_html=home_html.to_string() current_path="/".to_string() hero=hero.clone() /> }.to_html()
     });
     let site_structured = build_site_structured_data();
     let homepage_structured = build_homepage_structured_data();
@@ -139,23 +235,41 @@ pub(crate) fn prerender_top_page(home_html: &str) -> String {
             lazy_css_link(&asset_url("/assets/build/post-card.css")),
             // prose-base.css for below-fold content (lazy loaded, minimal styles for top page)
             lazy_css_link(&asset_url("/assets/build/prose-base.css")),
-            // Preload low-res hero candidates by viewport width
-            r#"<link rel="preload" as="image" type="image/avif" href="/assets/images/urumashi/urumashi-1280-low.avif" media="(max-width: 640px)" />"#.to_string(),
-            r#"<link rel="preload" as="image" type="image/avif" href="/assets/images/urumashi/urumashi-1920-low.avif" media="(min-width: 641px) and (max-width: 1024px)" />"#.to_string(),
-            r#"<link rel="preload" as="image" type="image/avif" href="/assets/images/urumashi/urumashi-2560-low.avif" media="(min-width: 1025px)" />"#.to_string(),
+            // Preload the hero in the format the page will pick first
+            hero_preload_link(hero.as_ref()),
             // Prefetch home.js for dynamic import
-            format!(r#"<link rel="prefetch" href="{href}" as="script" />"#, href=asset_url("/assets/build/home.js")),
+            format!(
+                r#"<link rel="prefetch" href="{href}" as="script" />"#,
+                href = asset_url("/assets/build/home.js")
+            ),
         ],
         head_scripts: vec![],
     };
     maybe_minify(wrap_html_with_options(
         &rendered,
-        "すずねーうのウェブサイト",
+        &config().site.title,
         &opts,
     ))
 }
 
-pub(crate) fn prerender_blog_page(meta: &FrontMatter, html_content: &str) -> String {
+fn hero_preload_link(hero: Option<&ResponsiveImage>) -> String {
+    match hero {
+        Some(image) => format!(
+            r#"<link rel="preload" as="image" type="image/avif" imagesrcset="{}" imagesizes="{HERO_IMAGE_SIZES}" fetchpriority="high" />"#,
+            ResponsiveImage::srcset(&image.avif)
+        ),
+        None => format!(
+            r#"<link rel="preload" as="image" href="{HERO_IMAGE_URL}" fetchpriority="high" />"#
+        ),
+    }
+}
+
+pub(crate) fn prerender_blog_page(
+    meta: &FrontMatter,
+    html_content: &str,
+    series: Option<&SeriesNav>,
+    read_next: Option<&PostNav>,
+) -> String {
     let rendered = Owner::new_root(None).with(|| {
         view! {
             <BlogPage
@@ -163,6 +277,8 @@ pub(crate) fn prerender_blog_page(meta: &FrontMatter, html_content: &str) -> Str
                 html_content=html_content.to_string()
                 meta=meta.clone()
                 current_path=format!("/blog/{}", meta.slug)
+                series=series.cloned()
+                read_next=read_next.cloned()
             />
         }
         .to_html()
@@ -170,12 +286,12 @@ pub(crate) fn prerender_blog_page(meta: &FrontMatter, html_content: &str) -> Str
 
     let page_title = meta
         .title
-        .as_ref()
-        .map(|t| format!("{t}｜すずねーう"))
-        .unwrap_or_else(|| "すずねーう".to_string());
+        .as_deref()
+        .map(page_title)
+        .unwrap_or_else(|| config().site.name.clone());
 
     let mut structured_vec = vec![build_site_structured_data()];
-    if let Some(a) = build_article_structured_data(meta) {
+    if let Some(a) = build_article_structured_data(meta, series) {
         structured_vec.push(a);
     }
     if let Some(bc) = build_breadcrumb_structured_data(
@@ -188,7 +304,24 @@ pub(crate) fn prerender_blog_page(meta: &FrontMatter, html_content: &str) -> Str
     let mut meta_map = meta.meta.clone();
     meta_map
         .entry("link:canonical".to_string())
-        .or_insert_with(|| format!("{SITE_URL}/blog/{}", meta.slug));
+        .or_insert_with(|| absolute_url(&format!("/blog/{}", meta.slug)));
+    if let Some(card) = meta.og_image.as_deref() {
+        // 生成した OG 画像（記事側で og:image を書いていればそちらが優先）
+        let card = absolute_url(card);
+        meta_map
+            .entry("og:image".to_string())
+            .or_insert_with(|| card.clone());
+        meta_map.entry("twitter:image".to_string()).or_insert(card);
+        meta_map
+            .entry("twitter:card".to_string())
+            .or_insert_with(|| "summary_large_image".to_string());
+    }
+    if !meta.is_listed() {
+        // 限定公開は URL を知っている人だけが見る想定
+        meta_map
+            .entry("robots".to_string())
+            .or_insert_with(|| "noindex".to_string());
+    }
 
     let opts = HtmlOptions {
         meta: Some(meta_map),
@@ -206,6 +339,8 @@ pub(crate) fn prerender_blog_page(meta: &FrontMatter, html_content: &str) -> Str
                 r#"<link rel="stylesheet" href="{href}" />"#,
                 href = asset_url("/assets/build/prose-full.css")
             ),
+            // 記事下の関連記事カード用
+            lazy_css_link(&asset_url("/assets/build/post-card.css")),
         ],
         head_scripts: vec![format!(
             r#"<script src="{href}" nonce="{CSP_NONCE_TOKEN}" defer data-rodin-twitter-loader="1"></script>"#,
@@ -218,19 +353,21 @@ pub(crate) fn prerender_blog_page(meta: &FrontMatter, html_content: &str) -> Str
 }
 
 pub(crate) fn prerender_profile_page(meta: &FrontMatter, profile_html: &str) -> String {
+    let description = format!("{}のプロフィールページ", config().site.author);
+    let title = page_title("プロフィール");
     let mut meta_map = meta.meta.clone();
     meta_map
         .entry("link:canonical".to_string())
-        .or_insert_with(|| format!("{SITE_URL}/profile"));
+        .or_insert_with(|| absolute_url("/profile"));
     meta_map
         .entry("description".to_string())
-        .or_insert_with(|| "すずねーうのプロフィールページ".to_string());
+        .or_insert_with(|| description.clone());
     meta_map
         .entry("og:description".to_string())
-        .or_insert_with(|| "すずねーうのプロフィールページ".to_string());
+        .or_insert(description);
     meta_map
         .entry("og:title".to_string())
-        .or_insert_with(|| "プロフィール｜すずねーう".to_string());
+        .or_insert_with(|| title.clone());
     meta_map
         .entry("og:type".to_string())
         .or_insert_with(|| "profile".to_string());
@@ -269,11 +406,7 @@ pub(crate) fn prerender_profile_page(meta: &FrontMatter, profile_html: &str) ->
         ],
         ..Default::default()
     };
-    maybe_minify(wrap_html_with_options(
-        &rendered,
-        "プロフィール｜すずねーう",
-        &opts,
-    ))
+    maybe_minify(wrap_html_with_options(&rendered, &title, &opts))
 }
 
 pub(crate) fn prerender_static_page(
@@ -285,7 +418,7 @@ pub(crate) fn prerender_static_page(
     let mut meta_map = meta.meta.clone();
     meta_map
         .entry("link:canonical".to_string())
-        .or_insert_with(|| format!("{SITE_URL}{}", path));
+        .or_insert_with(|| absolute_url(path));
     let fallback_desc = meta_map
         .get("description")
         .or_else(|| meta_map.get("og:description"))
@@ -338,14 +471,14 @@ pub(crate) fn prerender_static_page(
     };
     maybe_minify(wrap_html_with_options(
         &rendered,
-        &format!("{page_title}｜すずねーう"),
+        &format!("{page_title}｜{}", config().site.name),
         &opts,
     ))
 }
 
 pub(crate) fn render_search_page(
     query: String,
-    hits: &[SearchHit],
+    results: SearchResults,
     client_ip: &str,
     nonce: &str,
 ) -> String {
@@ -354,24 +487,30 @@ pub(crate) fn render_search_page(
             <crate::components::SearchPage
                 client_ip=client_ip.to_string()
                 query=query.clone()
-                results=hits.to_vec()
+                results=results
                 current_path="/search".to_string()
             />
         }
         .to_html()
     });
     let mut meta = HashMap::new();
-    meta.insert("link:canonical".to_string(), format!("{SITE_URL}/search"));
+    meta.insert("link:canonical".to_string(), absolute_url("/search"));
     meta.insert("robots".to_string(), "noindex, nofollow".to_string());
     let opts = HtmlOptions {
         meta: Some(meta),
-        head_links: vec![format!(
-            r#"<link rel="stylesheet" href="{href}" />"#,
-            href = asset_url("/assets/build/search.css")
-        )],
+        head_links: vec![
+            format!(
+                r#"<link rel="stylesheet" href="{href}" />"#,
+                href = asset_url("/assets/build/search.css")
+            ),
+            format!(
+                r#"<link rel="stylesheet" href="{href}" />"#,
+                href = asset_url("/assets/build/blog-list.css")
+            ),
+        ],
         ..Default::default()
     };
-    let html = wrap_html_with_options(&rendered, "検索｜すずねーう", &opts);
+    let html = wrap_html_with_options(&rendered, &page_title("検索"), &opts);
     inject_runtime_tokens(&html, client_ip, nonce)
 }
 
@@ -438,48 +577,53 @@ fn render_meta_tags(meta: &HashMap<String, String>) -> String {
 }
 
 fn top_meta() -> HashMap<String, String> {
+    let site = &config().site;
     let mut m = HashMap::new();
-    m.insert(
-        "description".to_string(),
-        "すずねーうのウェブサイト。Rust/Leptos/Typstで作ったブログとポートフォリオ".to_string(),
-    );
-    m.insert("og:description".to_string(), m["description"].clone());
-    m.insert(
-        "og:title".to_string(),
-        "すずねーうのウェブサイト".to_string(),
-    );
-    m.insert("og:site_name".to_string(), "すずねーう".to_string());
+    m.insert("description".to_string(), site.description.clone());
+    m.insert("og:description".to_string(), site.description.clone());
+    m.insert("og:title".to_string(), site.title.clone());
+    m.insert("og:site_name".to_string(), site.og_site_name.clone());
     m.insert("og:type".to_string(), "website".to_string());
-    m.insert("og:locale".to_string(), "ja_JP".to_string());
-    m.insert("link:canonical".to_string(), SITE_URL.to_string());
+    m.insert("og:locale".to_string(), site.locale.clone());
+    m.insert("link:canonical".to_string(), site.url.clone());
     m
 }
 
+/// `<title>` の「ページ名｜サイト名」
+fn page_title(title: &str) -> String {
+    format!("{title}｜{}", config().site.name)
+}
+
+fn org_id() -> String {
+    absolute_url("/#organization")
+}
+
 fn build_site_structured_data() -> String {
+    let site = &config().site;
     json!({
         "@context": "https://schema.org",
         "@graph": [
             {
                 "@type": "Organization",
-                "@id": ORG_ID,
-                "name": "すずねーう",
-                "url": SITE_URL,
+                "@id": org_id(),
+                "name": site.name,
+                "url": site.url,
                 "logo": {
                     "@type": "ImageObject",
-                    "@id": format!("{SITE_URL}/#logo"),
+                    "@id": absolute_url("/#logo"),
                     "url": absolute_url("/android-chrome-192x192.png")
                 }
             },
             {
                 "@type": "WebSite",
-                "@id": format!("{SITE_URL}/#website"),
-                "url": SITE_URL,
-                "name": "すずねーう",
-                "inLanguage": "ja",
-                "publisher": { "@id": ORG_ID },
+                "@id": absolute_url("/#website"),
+                "url": site.url,
+                "name": site.name,
+                "inLanguage": site.lang(),
+                "publisher": { "@id": org_id() },
                 "potentialAction": {
                     "@type": "SearchAction",
-                    "target": format!("{SITE_URL}/search?q={{query}}"),
+                    "target": absolute_url("/search?q={query}"),
                     "query-input": "required name=query"
                 }
             }
@@ -489,16 +633,17 @@ fn build_site_structured_data() -> String {
 }
 
 fn build_homepage_structured_data() -> String {
+    let site = &config().site;
     json!({
         "@context": "https://schema.org",
         "@type": "WebPage",
-        "@id": format!("{SITE_URL}/#webpage"),
-        "url": SITE_URL,
-        "name": "すずねーう",
-        "description": "すずねーうのウェブサイト。Rust/Leptos/Typstで作ったブログとポートフォリオ",
-        "inLanguage": "ja",
-        "isPartOf": { "@id": ORG_ID },
-        "primaryImageOfPage": format!("{SITE_URL}/android-chrome-192x192.png")
+        "@id": absolute_url("/#webpage"),
+        "url": site.url,
+        "name": site.name,
+        "description": site.description,
+        "inLanguage": site.lang(),
+        "isPartOf": { "@id": org_id() },
+        "primaryImageOfPage": absolute_url("/android-chrome-192x192.png")
     })
     .to_string()
 }
@@ -511,7 +656,7 @@ fn build_breadcrumb_structured_data(
     if meta.breadcrumbs.is_empty() {
         return None;
     }
-    let registry = breadcrumb_registry();
+    let registry = &config().breadcrumbs;
     let mut items = Vec::new();
     let mut pos = 1;
     for (idx, key) in meta.breadcrumbs.iter().enumerate() {
@@ -525,12 +670,12 @@ fn build_breadcrumb_structured_data(
                 "name": name,
                 "item": absolute_url(path),
             }));
-        } else if let Some((name, url)) = registry.get(key.as_str()) {
+        } else if let Some(crumb) = registry.get(key) {
             items.push(json!({
                 "@type": "ListItem",
                 "position": pos,
-                "name": name,
-                "item": absolute_url(url),
+                "name": crumb.name,
+                "item": absolute_url(&crumb.path),
             }));
         }
         pos += 1;
@@ -546,19 +691,7 @@ fn build_breadcrumb_structured_data(
     )
 }
 
-pub(crate) fn breadcrumb_registry(
-) -> std::collections::HashMap<&'static str, (&'static str, &'static str)> {
-    let mut m = std::collections::HashMap::new();
-    m.insert("home", ("ホーム", "/"));
-    m.insert("profile", ("プロフィール", "/profile"));
-    m.insert("blog", ("ブログ", "/blog"));
-    m.insert("tech", ("技術", "/tags/tech"));
-    m.insert("frontend", ("フロントエンド", "/tags/frontend"));
-    m.insert("backend", ("バックエンド", "/tags/backend"));
-    m
-}
-
-fn build_article_structured_data(meta: &FrontMatter) -> Option<String> {
+fn build_article_structured_data(meta: &FrontMatter, series: Option<&SeriesNav>) -> Option<String> {
     // Headline is the most important field; bail if we can't infer it.
     let headline = meta
         .title
@@ -572,20 +705,20 @@ fn build_article_structured_data(meta: &FrontMatter) -> Option<String> {
         .or_else(|| meta.meta.get("description"))
         .cloned();
 
-    // Prefer explicit OG image; otherwise fall back to site avatar.
+    // Prefer explicit OG image, then the generated card; otherwise fall back to site avatar.
     let mut images = Vec::new();
-    if let Some(img) = meta.meta.get("og:image") {
+    if let Some(img) = meta.meta.get("og:image").or(meta.og_image.as_ref()) {
         images.push(absolute_url(img));
     }
     if images.is_empty() {
-        images.push(absolute_url("/static/images/suzuneu.webp"));
+        images.push(absolute_url(&config().site.avatar));
     }
 
     let author_name = meta
         .meta
         .get("author")
         .cloned()
-        .unwrap_or_else(|| "すずねーう".to_string());
+        .unwrap_or_else(|| config().site.author.clone());
     let author_url = meta.meta.get("link:author").cloned();
 
     let published = meta
@@ -614,7 +747,7 @@ fn build_article_structured_data(meta: &FrontMatter) -> Option<String> {
         "url".into(),
         json!(absolute_url(&format!("/blog/{}", meta.slug))),
     );
-    obj.insert("inLanguage".into(), json!("ja"));
+    obj.insert("inLanguage".into(), json!(config().site.lang()));
     obj.insert("image".into(), json!(images));
 
     if let Some(desc) = description {
@@ -635,7 +768,7 @@ fn build_article_structured_data(meta: &FrontMatter) -> Option<String> {
     }
     obj.insert("author".into(), Value::Object(author_obj));
 
-    obj.insert("publisher".into(), json!({ "@id": ORG_ID }));
+    obj.insert("publisher".into(), json!({ "@id": org_id() }));
 
     if !meta.tags.is_empty() {
         obj.insert("keywords".into(), json!(meta.tags));
@@ -645,6 +778,17 @@ fn build_article_structured_data(meta: &FrontMatter) -> Option<String> {
             obj.insert("articleSection".into(), json!(section));
         }
     }
+    if let Some(series) = series {
+        obj.insert(
+            "isPartOf".into(),
+            json!({
+                "@type": "CreativeWorkSeries",
+                "name": series.id,
+                "url": absolute_url(&series_path(&series.id)),
+            }),
+        );
+        obj.insert("position".into(), json!(series.position));
+    }
 
     Some(Value::Object(obj).to_string())
 }
@@ -660,10 +804,8 @@ fn normalize_iso8601(date: &str) -> String {
 fn absolute_url(url: &str) -> String {
     if url.starts_with("http://") || url.starts_with("https://") {
         url.to_string()
-    } else if url.starts_with('/') {
-        format!("{SITE_URL}{url}")
     } else {
-        format!("{SITE_URL}/{}", url.trim_start_matches('/'))
+        format!("{}/{}", config().site.url, url.trim_start_matches('/'))
     }
 }
 
@@ -690,18 +832,15 @@ pub fn render_blog_list_page(
     current_page: u32,
     total_pages: u32,
 ) -> String {
+    let site = &config().site;
+    let description = format!("{}のブログ記事一覧", site.author);
+    let title = format!("Blog - {}", site.name);
     let mut meta = HashMap::new();
-    meta.insert(
-        "description".to_string(),
-        "すずねーうのブログ記事一覧".to_string(),
-    );
-    meta.insert("og:title".to_string(), "Blog - すずねーう".to_string());
-    meta.insert(
-        "og:description".to_string(),
-        "すずねーうのブログ記事一覧".to_string(),
-    );
+    meta.insert("description".to_string(), description.clone());
+    meta.insert("og:title".to_string(), title.clone());
+    meta.insert("og:description".to_string(), description);
     meta.insert("og:type".to_string(), "website".to_string());
-    meta.insert("og:url".to_string(), format!("{SITE_URL}/blog"));
+    meta.insert("og:url".to_string(), absolute_url("/blog"));
     meta.insert("twitter:card".to_string(), "summary".to_string());
 
     let opts = HtmlOptions {
@@ -723,14 +862,184 @@ pub fn render_blog_list_page(
         view! {
             <BlogListPage
                 client_ip=client_ip.to_string()
+                title="ブログ記事一覧".to_string()
                 posts=posts.clone()
                 current_page=current_page
                 total_pages=total_pages
+                base_url="/blog".to_string()
+            />
+        }
+        .to_html()
+    });
+
+    let html = wrap_html_with_options(&body, &title, &opts);
+    inject_runtime_tokens(&html, client_ip, nonce)
+}
+
+pub fn render_tags_page(client_ip: &str, nonce: &str, tags: Vec<TagSummary>) -> String {
+    let site = &config().site;
+    let description = format!("{}のブログのタグ一覧", site.author);
+    let title = format!("タグ一覧 - {}", site.name);
+    let mut meta = HashMap::new();
+    meta.insert("description".to_string(), description.clone());
+    meta.insert("og:title".to_string(), title.clone());
+    meta.insert("og:description".to_string(), description);
+    meta.insert("og:type".to_string(), "website".to_string());
+    meta.insert("og:url".to_string(), absolute_url("/tags"));
+    meta.insert("link:canonical".to_string(), absolute_url("/tags"));
+    meta.insert("twitter:card".to_string(), "summary".to_string());
+
+    let crumbs = FrontMatter {
+        breadcrumbs: vec!["home".to_string(), "tags".to_string()],
+        ..Default::default()
+    };
+    let opts = HtmlOptions {
+        meta: Some(meta),
+        structured_data: build_breadcrumb_structured_data(&crumbs, "/tags", "タグ")
+            .map(|bc| vec![build_site_structured_data(), bc]),
+        head_links: vec![format!(
+            r#"<link rel="stylesheet" href="{href}" />"#,
+            href = asset_url("/assets/build/blog-list.css")
+        )],
+        ..Default::default()
+    };
+
+    let body = Owner::new_root(None).with(|| {
+        view! { <TagsPage client_ip=client_ip.to_string() tags=tags.clone() /> }.to_html()
+    });
+
+    let html = wrap_html_with_options(&body, &title, &opts);
+    inject_runtime_tokens(&html, client_ip, nonce)
+}
+
+pub fn render_tag_page(
+    client_ip: &str,
+    nonce: &str,
+    tag: &str,
+    posts: Vec<BlogListItem>,
+    current_page: u32,
+    total_pages: u32,
+) -> String {
+    let path = tag_path(tag);
+    let canonical = if current_page > 1 {
+        absolute_url(&format!("{path}?page={current_page}"))
+    } else {
+        absolute_url(&path)
+    };
+    let site = &config().site;
+    let description = format!("{}のブログの「{tag}」タグが付いた記事一覧", site.author);
+    let page_title = format!("#{tag} - {}", site.name);
+
+    let mut meta = HashMap::new();
+    meta.insert("description".to_string(), description.clone());
+    meta.insert("og:title".to_string(), page_title.clone());
+    meta.insert("og:description".to_string(), description);
+    meta.insert("og:type".to_string(), "website".to_string());
+    meta.insert("og:url".to_string(), canonical.clone());
+    meta.insert("link:canonical".to_string(), canonical);
+    meta.insert("twitter:card".to_string(), "summary".to_string());
+
+    let crumbs = FrontMatter {
+        breadcrumbs: vec!["home".to_string(), "tags".to_string(), tag.to_string()],
+        ..Default::default()
+    };
+    let opts = HtmlOptions {
+        meta: Some(meta),
+        structured_data: build_breadcrumb_structured_data(&crumbs, &path, &format!("#{tag}"))
+            .map(|bc| vec![build_site_structured_data(), bc]),
+        head_links: vec![
+            feed_alternate_links(&path, &page_title),
+            format!(
+                r#"<link rel="stylesheet" href="{href}" />"#,
+                href = asset_url("/assets/build/post-card.css")
+            ),
+            format!(
+                r#"<link rel="stylesheet" href="{href}" />"#,
+                href = asset_url("/assets/build/blog-list.css")
+            ),
+        ],
+        ..Default::default()
+    };
+
+    let body = Owner::new_root(None).with(|| {
+        view! {
+            <BlogListPage
+                client_ip=client_ip.to_string()
+                title=format!("#{tag} の記事一覧")
+                posts=posts.clone()
+                current_page=current_page
+                total_pages=total_pages
+                base_url=path.clone()
+            />
+        }
+        .to_html()
+    });
+
+    let html = wrap_html_with_options(&body, &page_title, &opts);
+    inject_runtime_tokens(&html, client_ip, nonce)
+}
+
+pub fn render_series_page(
+    client_ip: &str,
+    nonce: &str,
+    id: &str,
+    posts: Vec<BlogListItem>,
+) -> String {
+    let path = series_path(id);
+    let canonical = absolute_url(&path);
+    let site = &config().site;
+    let description = format!(
+        "{}のブログの連載「{id}」の記事一覧（{} 回）",
+        site.author,
+        posts.len()
+    );
+    let page_title = format!("連載「{id}」 - {}", site.name);
+
+    let mut meta = HashMap::new();
+    meta.insert("description".to_string(), description.clone());
+    meta.insert("og:title".to_string(), page_title.clone());
+    meta.insert("og:description".to_string(), description);
+    meta.insert("og:type".to_string(), "website".to_string());
+    meta.insert("og:url".to_string(), canonical.clone());
+    meta.insert("link:canonical".to_string(), canonical);
+    meta.insert("twitter:card".to_string(), "summary".to_string());
+
+    let crumbs = FrontMatter {
+        breadcrumbs: vec!["home".to_string(), "blog".to_string(), id.to_string()],
+        ..Default::default()
+    };
+    let opts = HtmlOptions {
+        meta: Some(meta),
+        structured_data: build_breadcrumb_structured_data(&crumbs, &path, &format!("連載「{id}」"))
+            .map(|bc| vec![build_site_structured_data(), bc]),
+        head_links: vec![
+            format!(
+                r#"<link rel="stylesheet" href="{href}" />"#,
+                href = asset_url("/assets/build/post-card.css")
+            ),
+            format!(
+                r#"<link rel="stylesheet" href="{href}" />"#,
+                href = asset_url("/assets/build/blog-list.css")
+            ),
+        ],
+        ..Default::default()
+    };
+
+    // 連載は順番に読むものなのでページ分割しない
+    let body = Owner::new_root(None).with(|| {
+        view! {
+            <BlogListPage
+                client_ip=client_ip.to_string()
+                title=format!("連載「{id}」")
+                posts=posts.clone()
+                current_page=1
+                total_pages=1
+                base_url=path.clone()
             />
         }
         .to_html()
     });
 
-    let html = wrap_html_with_options(&body, "Blog - すずねーう", &opts);
+    let html = wrap_html_with_options(&body, &page_title, &opts);
     inject_runtime_tokens(&html, client_ip, nonce)
 }
@@ -0,0 +1,742 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset, Utc};
+use unicode_normalization::{char::compose, UnicodeNormalization};
+
+use super::state::SearchIndexEntry;
+use crate::frontmatter::parse_datetime;
+
+mod query;
+mod snippet;
+pub use query::{parse as parse_query, Query};
+use query::{Clause, Filter};
+pub use snippet::snippet;
+
+/// 索引に入れる欄（`Posting::tf` と `FIELD_BOOSTS` の添字）
+const TITLE: usize = 0;
+const TAGS: usize = 1;
+const DESCRIPTION: usize = 2;
+const BODY: usize = 3;
+const FIELDS: usize = 4;
+/// 欄ごとの重み（タイトル・タグ・説明文に出る語を本文より重く見る）
+const FIELD_BOOSTS: [f32; FIELDS] = [3.0, 2.5, 1.5, 1.0];
+/// BM25 のパラメータ
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+/// 前方一致で拾っただけの語（`rust` に対する `rustacean` など）は軽く見る
+const PREFIX_WEIGHT: f32 = 0.5;
+/// 綴りの近さで拾った語（`rsut` に対する `rust` など）はもっと軽く見る
+const FUZZY_WEIGHT: f32 = 0.3;
+/// 新しい記事への上乗せ（公開直後で最大 1 割、1 年ごとに半分）
+const FRESHNESS_BOOST: f32 = 0.1;
+const FRESHNESS_HALF_LIFE_DAYS: f32 = 365.0;
+
+/// 1 語が 1 記事の各欄に出てくる回数
+#[derive(Clone, Copy)]
+struct Posting {
+    doc: u32,
+    tf: [u16; FIELDS],
+    /// `SearchIndex::positions` の中で出てくる位置が始まるところ（次の posting の手前まで）
+    positions: u32,
+}
+
+/// 構築中の posting（doc id、欄ごとの回数、位置）
+type PendingPosting = (u32, [u16; FIELDS], Vec<u32>);
+
+/// 記事ごとの欄の長さ（語数）と公開日
+struct DocStats {
+    lens: [u32; FIELDS],
+    published: Option<DateTime<FixedOffset>>,
+}
+
+/// 記事の転置インデックス
+///
+/// 日本語（漢字・かな・ハングル）は文字 bigram、それ以外は英数字の単語を語にする。
+/// 語は辞書順に並べて二分探索で引き、postings は 1 本の配列にまとめて持つ。
+/// 語の位置も差分を詰めて持ち、bigram や語の続き具合は本文を読み直さずに確かめる。
+/// 順位は欄ごとに重みを付けた BM25（BM25F）に、新しさを少しだけ掛けて決める。
+pub struct SearchIndex {
+    /// doc id は添字
+    entries: Vec<SearchIndexEntry>,
+    docs: Vec<DocStats>,
+    /// 欄ごとの平均の長さ
+    avg_lens: [f32; FIELDS],
+    terms: Vec<Box<str>>,
+    /// `terms[i]` の postings は `postings[offsets[i]..offsets[i + 1]]`
+    offsets: Vec<u32>,
+    postings: Vec<Posting>,
+    /// postings の順に、記事の中で語が出てくる位置（昇順の差分を LEB128 で）
+    positions: Vec<u8>,
+}
+
+/// 検索結果 1 件
+pub struct Ranked<'a> {
+    pub entry: &'a SearchIndexEntry,
+    pub score: f32,
+}
+
+/// OR グループの中の 1 項
+enum Alternative<'q> {
+    Text(Vec<(u32, f32)>),
+    Filter(&'q Filter),
+}
+
+/// 検索語 1 つ分の引き方
+struct QueryTerm {
+    text: String,
+    /// 英単語の打ちかけや 1 文字の漢字は前方一致で引く
+    prefix: bool,
+    /// 検索語の中での位置（索引の位置と同じ数え方）
+    offset: u32,
+    /// 索引にないときに綴りの近い語で引いてよいか（英単語だけ）
+    fuzzy: bool,
+}
+
+impl SearchIndex {
+    pub fn build(entries: Vec<SearchIndexEntry>) -> Self {
+        let mut table: HashMap<String, Vec<PendingPosting>> = HashMap::new();
+        let mut docs = Vec::with_capacity(entries.len());
+        for (doc, entry) in entries.iter().enumerate() {
+            let mut fields: [Vec<(String, u32)>; FIELDS] = Default::default();
+            fields[TITLE] = tokenize(&entry.title);
+            fields[TAGS] = tokenize(&entry.tags.join(" "));
+            fields[DESCRIPTION] = tokenize(entry.description.as_deref().unwrap_or_default());
+            fields[BODY] = tokenize(&entry.body);
+
+            // 位置は欄を通して数え、欄の間は 1 つ空けて続けて読めないようにする
+            let mut counts: HashMap<&str, ([u16; FIELDS], Vec<u32>)> = HashMap::new();
+            let mut base = 0;
+            for (field, terms) in fields.iter().enumerate() {
+                for (term, pos) in terms {
+                    let (tf, positions) = counts.entry(term).or_default();
+                    tf[field] = tf[field].saturating_add(1);
+                    positions.push(base + pos);
+                }
+                base += terms.last().map_or(0, |(_, pos)| pos + 1) + 1;
+            }
+            // 記事の順に積むので postings は doc id の昇順になる
+            for (term, (tf, positions)) in counts {
+                table
+                    .entry(term.to_string())
+                    .or_default()
+                    .push((doc as u32, tf, positions));
+            }
+            docs.push(DocStats {
+                lens: fields.each_ref().map(|terms| terms.len() as u32),
+                published: entry.published_at.as_deref().and_then(parse_datetime),
+            });
+        }
+
+        let mut avg_lens = [0.0; FIELDS];
+        for (field, avg) in avg_lens.iter_mut().enumerate() {
+            let total: u64 = docs.iter().map(|d| u64::from(d.lens[field])).sum();
+            *avg = (total as f32 / docs.len().max(1) as f32).max(1.0);
+        }
+
+        let mut sorted: Vec<_> = table.into_iter().collect();
+        sorted.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        let mut terms = Vec::with_capacity(sorted.len());
+        let mut offsets = Vec::with_capacity(sorted.len() + 1);
+        let mut postings = Vec::with_capacity(sorted.iter().map(|(_, p)| p.len()).sum());
+        let mut positions = Vec::new();
+        offsets.push(0);
+        for (term, list) in sorted {
+            terms.push(term.into_boxed_str());
+            for (doc, tf, doc_positions) in list {
+                postings.push(Posting {
+                    doc,
+                    tf,
+                    positions: positions.len() as u32,
+                });
+                encode_positions(&doc_positions, &mut positions);
+            }
+            offsets.push(postings.len() as u32);
+        }
+
+        SearchIndex {
+            entries,
+            docs,
+            avg_lens,
+            terms,
+            offsets,
+            postings,
+            positions,
+        }
+    }
+
+    /// 一覧・タグ・連載ページ用に記事を並べたもの
+    pub fn entries(&self) -> &[SearchIndexEntry] {
+        &self.entries
+    }
+
+    /// 検索式に当てはまる記事（スコアの高い順）
+    ///
+    /// 語は postings で引いて点数を付け、語だけのグループがあればその候補から、
+    /// なければ全記事から、残りのグループと除外・絞り込みで落としていく。
+    pub fn search(&self, query: &Query, now: DateTime<Utc>) -> Vec<Ranked<'_>> {
+        let groups: Vec<Vec<Alternative>> = query
+            .groups
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|c| self.alternative(c, query.fuzzy))
+                    .collect()
+            })
+            .collect();
+        let excluded: Vec<Alternative> = query
+            .excluded
+            .iter()
+            .map(|c| self.alternative(c, false))
+            .collect();
+
+        let seed: Vec<u32> = groups
+            .iter()
+            .filter_map(|alts| {
+                let lists: Option<Vec<&Vec<(u32, f32)>>> = alts
+                    .iter()
+                    .map(|alt| match alt {
+                        Alternative::Text(list) => Some(list),
+                        Alternative::Filter(_) => None,
+                    })
+                    .collect();
+                lists
+            })
+            .min_by_key(|lists| lists.iter().map(|l| l.len()).sum::<usize>())
+            .map(|lists| {
+                let mut docs: Vec<u32> = lists.iter().flat_map(|l| l.iter().map(|p| p.0)).collect();
+                docs.sort_unstable();
+                docs.dedup();
+                docs
+            })
+            .unwrap_or_else(|| (0..self.entries.len() as u32).collect());
+
+        let mut ranked: Vec<Ranked> = seed
+            .into_iter()
+            .filter_map(|doc| {
+                let doc = doc as usize;
+                if excluded
+                    .iter()
+                    .any(|alt| self.alternative_score(alt, doc).is_some())
+                {
+                    return None;
+                }
+                let mut score = 0.0;
+                for alts in &groups {
+                    // OR は当たった中で一番高い点
+                    score += alts
+                        .iter()
+                        .filter_map(|alt| self.alternative_score(alt, doc))
+                        .reduce(f32::max)?;
+                }
+                Some(Ranked {
+                    entry: &self.entries[doc],
+                    score: score * self.freshness(doc, now),
+                })
+            })
+            .collect();
+        // 同点なら新しい順（doc id の順）のまま
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        ranked
+    }
+
+    fn alternative<'q>(&self, clause: &'q Clause, fuzzy: bool) -> Alternative<'q> {
+        match clause {
+            Clause::Text { text, phrase } => {
+                Alternative::Text(self.text_matches(text, *phrase, fuzzy))
+            }
+            Clause::Filter(filter) => Alternative::Filter(filter),
+        }
+    }
+
+    fn alternative_score(&self, alt: &Alternative, doc: usize) -> Option<f32> {
+        match alt {
+            Alternative::Text(list) => list
+                .binary_search_by_key(&(doc as u32), |p| p.0)
+                .ok()
+                .map(|i| list[i].1),
+            Alternative::Filter(filter) => filter
+                .matches(&self.entries[doc], self.docs[doc].published)
+                .then_some(0.0),
+        }
+    }
+
+    /// 語 1 つ（または `"..."` の並び）に当たる記事と点数（doc id の昇順）
+    ///
+    /// 候補は postings の共通部分で絞り、bigram や複数の語に分かれたものだけ語の位置で続き具合を確かめる。
+    fn text_matches(&self, text: &str, phrase: bool, fuzzy: bool) -> Vec<(u32, f32)> {
+        let text = normalize(text);
+        let mut terms = query_terms(&text);
+        let verify = phrase || terms.len() > 1;
+        // 続き具合を確かめるものは綴りどおりでないと当たらない
+        if verify || !fuzzy {
+            terms.iter_mut().for_each(|t| t.fuzzy = false);
+        }
+        let mut candidates: Option<Vec<(u32, f32)>> = None;
+        for term in &terms {
+            let scored = self.score_term(term);
+            candidates = Some(match candidates {
+                Some(current) => intersect(&current, &scored),
+                None => scored,
+            });
+            if candidates.as_ref().is_some_and(Vec::is_empty) {
+                break;
+            }
+        }
+        let mut matches = candidates.unwrap_or_default();
+        if verify {
+            let expanded: Vec<Vec<(usize, f32)>> =
+                terms.iter().map(|t| self.expansions(t)).collect();
+            matches.retain(|&(doc, _)| self.adjacent(doc, &terms, &expanded));
+        }
+        matches
+    }
+
+    /// 記事の中に、語が検索語と同じ間隔で並んでいるところがあるか
+    fn adjacent(&self, doc: u32, terms: &[QueryTerm], expanded: &[Vec<(usize, f32)>]) -> bool {
+        let lists: Vec<Vec<u32>> = expanded
+            .iter()
+            .map(|expansions| {
+                let mut list: Vec<u32> = expansions
+                    .iter()
+                    .flat_map(|&(i, _)| self.doc_positions(i, doc))
+                    .collect();
+                list.sort_unstable();
+                list
+            })
+            .collect();
+        let Some(first) = lists.first() else {
+            return false;
+        };
+        first.iter().any(|&pos| {
+            let start = pos - terms[0].offset;
+            terms
+                .iter()
+                .zip(&lists)
+                .skip(1)
+                .all(|(term, list)| list.binary_search(&(start + term.offset)).is_ok())
+        })
+    }
+
+    /// 語 `term` が記事 `doc` に出てくる位置（昇順）
+    fn doc_positions(&self, term: usize, doc: u32) -> Vec<u32> {
+        let Ok(i) = self.postings_of(term).binary_search_by_key(&doc, |p| p.doc) else {
+            return Vec::new();
+        };
+        let index = self.offsets[term] as usize + i;
+        let start = self.postings[index].positions as usize;
+        let end = self
+            .postings
+            .get(index + 1)
+            .map_or(self.positions.len(), |p| p.positions as usize);
+        decode_positions(&self.positions[start..end])
+    }
+
+    /// 1 語の BM25F スコア（doc id の昇順）
+    fn score_term(&self, term: &QueryTerm) -> Vec<(u32, f32)> {
+        // 広げた語の出現はまとめて 1 語として数える
+        let mut weighted: Vec<(u32, f32)> = Vec::new();
+        for (i, weight) in self.expansions(term) {
+            weighted.extend(
+                self.postings_of(i)
+                    .iter()
+                    .map(|p| (p.doc, weight * self.weighted_tf(p))),
+            );
+        }
+        weighted.sort_unstable_by_key(|&(doc, _)| doc);
+        weighted.dedup_by(|next, kept| {
+            let same = next.0 == kept.0;
+            if same {
+                kept.1 += next.1;
+            }
+            same
+        });
+
+        let n = self.entries.len() as f32;
+        let df = weighted.len() as f32;
+        let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+        weighted
+            .into_iter()
+            .map(|(doc, tf)| (doc, idf * tf * (K1 + 1.0) / (K1 + tf)))
+            .collect()
+    }
+
+    /// 検索語 1 つで引く索引の語と重み（前方一致・綴りの近い語に広げたもの）
+    fn expansions(&self, term: &QueryTerm) -> Vec<(usize, f32)> {
+        let text = term.text.as_str();
+        let start = self.terms.partition_point(|t| &**t < text);
+        let end = if term.prefix {
+            start + self.terms[start..].partition_point(|t| t.starts_with(text))
+        } else {
+            start + usize::from(self.terms.get(start).is_some_and(|t| &**t == text))
+        };
+        let mut expansions: Vec<(usize, f32)> = (start..end)
+            .map(|i| {
+                let weight = if &*self.terms[i] == text {
+                    1.0
+                } else {
+                    PREFIX_WEIGHT
+                };
+                (i, weight)
+            })
+            .collect();
+        // 打ち間違いは、そのままでは 1 語も引けないときだけ綴りの近い語で拾う
+        if expansions.is_empty() && term.fuzzy {
+            expansions = self
+                .fuzzy_terms(text)
+                .into_iter()
+                .map(|i| (i, FUZZY_WEIGHT))
+                .collect();
+        }
+        expansions
+    }
+
+    /// 先頭の文字が同じで、編集距離（隣どうしの入れ替えも 1 と数える）が近い語
+    fn fuzzy_terms(&self, text: &str) -> Vec<usize> {
+        let query: Vec<char> = text.chars().collect();
+        let max = match query.len() {
+            0..=3 => return Vec::new(),
+            4..=7 => 1,
+            _ => 2,
+        };
+        let first = &text[..query[0].len_utf8()];
+        let start = self.terms.partition_point(|t| &**t < first);
+        let end = start + self.terms[start..].partition_point(|t| t.starts_with(first));
+        (start..end)
+            .filter(|&i| {
+                let term: Vec<char> = self.terms[i].chars().collect();
+                term.len().abs_diff(query.len()) <= max && edit_distance(&query, &term) <= max
+            })
+            .collect()
+    }
+
+    /// 欄の長さで正規化して重みを掛けた出現回数
+    fn weighted_tf(&self, posting: &Posting) -> f32 {
+        let lens = &self.docs[posting.doc as usize].lens;
+        (0..FIELDS)
+            .filter(|&f| posting.tf[f] > 0)
+            .map(|f| {
+                let norm = 1.0 - B + B * lens[f] as f32 / self.avg_lens[f];
+                FIELD_BOOSTS[f] * f32::from(posting.tf[f]) / norm
+            })
+            .sum()
+    }
+
+    fn freshness(&self, doc: usize, now: DateTime<Utc>) -> f32 {
+        let Some(published) = self.docs[doc].published else {
+            return 1.0;
+        };
+        let days = (now - published.with_timezone(&Utc)).num_days().max(0) as f32;
+        1.0 + FRESHNESS_BOOST * 0.5f32.powf(days / FRESHNESS_HALF_LIFE_DAYS)
+    }
+
+    fn postings_of(&self, term: usize) -> &[Posting] {
+        let (start, end) = (self.offsets[term], self.offsets[term + 1]);
+        &self.postings[start as usize..end as usize]
+    }
+}
+
+/// 索引と検索語で同じ表記にそろえる
+///
+/// NFKC（全角英数・半角カナなど）→ 分かれた濁点や結合文字をまとめる → 小文字 →
+/// カタカナをひらがなに → 長音符を落とす、の順。
+/// 文字ごとに元の文字列の何文字目から来たかを付けて返す。
+fn normalized_chars(text: &str) -> Vec<(char, usize)> {
+    let mut out: Vec<(char, usize)> = Vec::with_capacity(text.len());
+    for (i, c) in text.chars().enumerate() {
+        for n in std::iter::once(c).nfkc() {
+            // ｶﾞ や「か + U+3099」は 1 文字にする
+            if let Some(last) = out.last_mut() {
+                if let Some(composed) = compose(last.0, n) {
+                    last.0 = composed;
+                    continue;
+                }
+            }
+            out.extend(n.to_lowercase().filter_map(fold_kana).map(|f| (f, i)));
+        }
+    }
+    out
+}
+
+fn normalize(text: &str) -> String {
+    normalized_chars(text).into_iter().map(|(c, _)| c).collect()
+}
+
+/// カタカナはひらがなに、長音符は落とす（「サーバー」と「サーバ」を同じにする）
+fn fold_kana(c: char) -> Option<char> {
+    match c {
+        'ー' => None,
+        'ァ'..='ヶ' => char::from_u32(c as u32 - 0x60),
+        _ => Some(c),
+    }
+}
+
+/// 漢字・かな・ハングル（語の区切りがないので bigram にする）
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3005}' | '\u{3007}'
+        | '\u{3040}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF66}'..='\u{FF9F}')
+}
+
+/// 英数字の単語か、日本語の連なり
+enum Segment {
+    Word(String),
+    Run(Vec<char>),
+}
+
+/// 正規化した文字列を単語と日本語の連なりに分ける（記号や空白は区切り）
+fn segments(text: &str) -> Vec<Segment> {
+    let mut out = Vec::new();
+    let mut word = String::new();
+    let mut run: Vec<char> = Vec::new();
+    for c in text.chars().chain([' ']) {
+        if !is_cjk(c) && !run.is_empty() {
+            out.push(Segment::Run(std::mem::take(&mut run)));
+        }
+        if (is_cjk(c) || !c.is_alphanumeric()) && !word.is_empty() {
+            out.push(Segment::Word(std::mem::take(&mut word)));
+        }
+        if is_cjk(c) {
+            run.push(c);
+        } else if c.is_alphanumeric() {
+            word.push(c);
+        }
+    }
+    out
+}
+
+/// 索引に入れる語と位置
+///
+/// 日本語の連なりは bigram に加えて最後の 1 文字も入れ、どの文字もいずれかの語の先頭に来るようにする
+/// （1 文字の検索を前方一致で引けるように）。位置は英数字の単語なら 1 語、日本語なら 1 文字ごとに進む。
+fn tokenize(text: &str) -> Vec<(String, u32)> {
+    let mut terms = Vec::new();
+    let mut pos = 0;
+    for segment in segments(&normalize(text)) {
+        match segment {
+            Segment::Word(word) => {
+                terms.push((word, pos));
+                pos += 1;
+            }
+            Segment::Run(run) => {
+                let len = run.len() as u32;
+                terms.extend(
+                    run.windows(2)
+                        .zip(pos..)
+                        .map(|(w, p)| (w.iter().collect(), p)),
+                );
+                terms.extend(run.last().map(|c| (c.to_string(), pos + len - 1)));
+                pos += len;
+            }
+        }
+    }
+    terms
+}
+
+/// 検索語 1 つを引く語に分ける（正規化済みの語を受け取る）
+fn query_terms(word: &str) -> Vec<QueryTerm> {
+    let mut terms = Vec::new();
+    let mut offset = 0;
+    for segment in segments(word) {
+        match segment {
+            Segment::Word(text) => {
+                terms.push(QueryTerm {
+                    fuzzy: text.chars().any(char::is_alphabetic),
+                    text,
+                    prefix: true,
+                    offset,
+                });
+                offset += 1;
+            }
+            Segment::Run(run) if run.len() == 1 => {
+                terms.push(QueryTerm {
+                    text: run[0].to_string(),
+                    prefix: true,
+                    fuzzy: false,
+                    offset,
+                });
+                offset += 1;
+            }
+            Segment::Run(run) => {
+                terms.extend(run.windows(2).zip(offset..).map(|(w, o)| QueryTerm {
+                    text: w.iter().collect(),
+                    prefix: false,
+                    fuzzy: false,
+                    offset: o,
+                }));
+                offset += run.len() as u32;
+            }
+        }
+    }
+    terms
+}
+
+/// 昇順の位置を、前との差の LEB128 で詰める
+fn encode_positions(list: &[u32], out: &mut Vec<u8>) {
+    let mut prev = 0;
+    for &pos in list {
+        let mut delta = pos - prev;
+        prev = pos;
+        while delta >= 0x80 {
+            out.push((delta & 0x7f) as u8 | 0x80);
+            delta >>= 7;
+        }
+        out.push(delta as u8);
+    }
+}
+
+fn decode_positions(bytes: &[u8]) -> Vec<u32> {
+    let mut out = Vec::new();
+    let (mut prev, mut delta, mut shift) = (0u32, 0u32, 0);
+    for &byte in bytes {
+        delta |= u32::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            prev += delta;
+            out.push(prev);
+            (delta, shift) = (0, 0);
+        } else {
+            shift += 7;
+        }
+    }
+    out
+}
+
+/// 昇順の doc id 同士の共通部分（スコアは足し合わせる）
+fn intersect(a: &[(u32, f32)], b: &[(u32, f32)]) -> Vec<(u32, f32)> {
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::with_capacity(a.len().min(b.len()));
+    while i < a.len() && j < b.len() {
+        match a[i].0.cmp(&b[j].0) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                out.push((a[i].0, a[i].1 + b[j].1));
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    out
+}
+
+/// 隣どうしの入れ替えも 1 回と数える編集距離（optimal string alignment）
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut prev2: Vec<usize> = Vec::new();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(prev2[j - 2] + 1);
+            }
+        }
+        prev2 = std::mem::replace(&mut prev, row);
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    fn entry(title: &str, body: &str) -> SearchIndexEntry {
+        SearchIndexEntry {
+            slug: title.to_string(),
+            title: title.to_string(),
+            published_at: None,
+            updated_at: None,
+            description: None,
+            tags: Vec::new(),
+            genre: None,
+            body: body.into(),
+            sections: Box::new([]),
+            listed: true,
+            publish_at: None,
+        }
+    }
+
+    #[test]
+    fn transposition_counts_as_one_edit() {
+        assert_eq!(edit_distance(&chars("rust"), &chars("rsut")), 1);
+        assert_eq!(edit_distance(&chars("typst"), &chars("tpyts")), 2);
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+        assert_eq!(edit_distance(&chars("same"), &chars("same")), 0);
+    }
+
+    #[test]
+    fn normalizes_width_case_and_kana() {
+        assert_eq!(normalize("ＲＵＳＴ Rust"), "rust rust");
+        assert_eq!(normalize("カタカナ"), "かたかな");
+        assert_eq!(normalize("ｶﾞｲﾄﾞ"), "がいど");
+        assert_eq!(normalize("か\u{3099}"), "が");
+        // 長音符はあってもなくても同じ
+        assert_eq!(normalize("サーバー"), normalize("サーバ"));
+    }
+
+    #[test]
+    fn normalized_chars_point_back_to_the_original() {
+        // ｶﾞ（2 文字）は 0 文字目の 1 文字に、ー は消える
+        assert_eq!(normalized_chars("ｶﾞーA"), [('が', 0), ('a', 3)]);
+    }
+
+    #[test]
+    fn tokenize_splits_words_and_bigrams() {
+        let expected = |list: &[(&str, u32)]| -> Vec<(String, u32)> {
+            list.iter().map(|&(t, p)| (t.to_string(), p)).collect()
+        };
+        // 日本語は 1 文字ごと、英数字は 1 語ごとに位置が進む
+        assert_eq!(
+            tokenize("Rustの検索、C++"),
+            expected(&[("rust", 0), ("の検", 1), ("検索", 2), ("索", 3), ("c", 4)])
+        );
+        assert_eq!(tokenize("サーバー"), expected(&[("さば", 0), ("ば", 1)]));
+    }
+
+    #[test]
+    fn positions_round_trip() {
+        let list = [0, 1, 127, 128, 300, 16_384, u32::MAX];
+        let mut bytes = Vec::new();
+        encode_positions(&list, &mut bytes);
+        assert_eq!(decode_positions(&bytes), list);
+    }
+
+    #[test]
+    fn phrase_needs_adjacent_terms() {
+        let index = SearchIndex::build(vec![
+            entry("a", "全文検索エンジンを作る。Rust async runtime"),
+            entry("b", "検索の話とエンジンの話。async Rust"),
+            entry("c", "索引の検索rust"),
+        ]);
+        let hits = |q: &str| -> Vec<String> {
+            let query =
+                parse_query(q, FixedOffset::east_opt(0).unwrap()).unwrap_or_else(|e| panic!("{e}"));
+            let mut slugs: Vec<String> = index
+                .search(&query, Utc::now())
+                .iter()
+                .map(|r| r.entry.slug.clone())
+                .collect();
+            slugs.sort();
+            slugs
+        };
+        assert_eq!(hits("検索エンジン"), ["a"]);
+        assert_eq!(hits("検索 エンジン"), ["a", "b"]);
+        assert_eq!(hits("\"エンジンを\""), ["a"]);
+        assert_eq!(hits("\"rust async\""), ["a"]);
+        assert_eq!(hits("\"rust asy\""), ["a"]);
+        assert_eq!(hits("検索rust"), ["c"]);
+        // 欄をまたいではつながらない
+        assert!(hits("\"c 索引\"").is_empty());
+    }
+}
@@ -0,0 +1,488 @@
+use std::{fmt, ops::Range};
+
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone};
+
+use super::normalize;
+use crate::app::state::SearchIndexEntry;
+use crate::frontmatter::tag_key;
+
+/// 絞り込みに使えるキー（`tag:rust` の `tag`）と、`fuzzy:off`
+const FILTER_KEYS: &[&str] = &["tag", "genre", "before", "after", "fuzzy"];
+
+/// `/search?q=` の検索式
+///
+/// 空白で区切った項はすべて満たす記事を探し、`OR` でつないだ項はどれか 1 つでよい。
+/// `"..."` は続けて出てくる語、`-` を付けた項は当たる記事を外す。
+/// `fuzzy:off` で綴りの近い語を拾わないようにする。
+pub struct Query {
+    /// グループの中は OR、グループどうしは AND
+    pub groups: Vec<Vec<Clause>>,
+    pub excluded: Vec<Clause>,
+    pub fuzzy: bool,
+    /// 画面にチップとして出す絞り込み
+    pub filters: Vec<ActiveFilter>,
+}
+
+pub enum Clause {
+    Text { text: String, phrase: bool },
+    Filter(Filter),
+}
+
+pub enum Filter {
+    /// `tag_key` でそろえたタグ
+    Tag(String),
+    /// 小文字にそろえたジャンル
+    Genre(String),
+    /// この時刻より前に公開
+    Before(DateTime<FixedOffset>),
+    /// この時刻以降に公開
+    After(DateTime<FixedOffset>),
+}
+
+/// 式の中の絞り込み（`span` は元の式でのバイト位置。チップから外すのに使う）
+pub struct ActiveFilter {
+    pub label: String,
+    pub span: Range<usize>,
+}
+
+/// 読めない検索式（メッセージはそのまま画面に出す）
+#[derive(Debug)]
+pub struct QueryError(String);
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Query {
+    /// スニペットで探す語（除外した語は含めない）
+    pub fn needles(&self) -> Vec<&str> {
+        self.groups
+            .iter()
+            .flatten()
+            .filter_map(|clause| match clause {
+                Clause::Text { text, .. } => Some(text.as_str()),
+                Clause::Filter(_) => None,
+            })
+            .collect()
+    }
+}
+
+impl Filter {
+    pub fn matches(
+        &self,
+        entry: &SearchIndexEntry,
+        published: Option<DateTime<FixedOffset>>,
+    ) -> bool {
+        match self {
+            Filter::Tag(key) => entry.tags.iter().any(|t| tag_key(t) == *key),
+            Filter::Genre(genre) => entry
+                .genre
+                .as_deref()
+                .is_some_and(|g| g.trim().to_lowercase() == *genre),
+            Filter::Before(at) => published.is_some_and(|p| p < *at),
+            Filter::After(at) => published.is_some_and(|p| p >= *at),
+        }
+    }
+}
+
+enum TokenKind {
+    Or,
+    Text { text: String, phrase: bool },
+    Filter { key: &'static str, value: String },
+}
+
+struct Token {
+    kind: TokenKind,
+    negated: bool,
+    span: Range<usize>,
+}
+
+/// 組み立て中の OR グループ
+struct Group {
+    clauses: Vec<Clause>,
+    /// 絞り込みだけのグループならチップの見出し
+    labels: Option<Vec<String>>,
+    span: Range<usize>,
+}
+
+/// 直前に読んだ項
+#[derive(Clone, Copy, PartialEq)]
+enum Prev {
+    Positive,
+    Negated,
+    Or,
+}
+
+/// 日付は `offset`（サイトのタイムゾーン）の 0 時として読む
+pub fn parse(input: &str, offset: FixedOffset) -> Result<Query, QueryError> {
+    let mut groups: Vec<Group> = Vec::new();
+    let mut excluded = Vec::new();
+    let mut filters = Vec::new();
+    let mut fuzzy = true;
+    let mut prev: Option<Prev> = None;
+    for token in lex(input)? {
+        let (clause, label) = match token.kind {
+            TokenKind::Or => {
+                match prev {
+                    Some(Prev::Positive) => {}
+                    None => return Err(error("OR の前に語がありません")),
+                    Some(Prev::Or) => return Err(error("OR が続いています")),
+                    Some(Prev::Negated) => return Err(error(NEGATED_OR)),
+                }
+                prev = Some(Prev::Or);
+                continue;
+            }
+            // 記号や長音符だけの語は索引に語がないので読み飛ばす
+            TokenKind::Text { text, .. }
+                if !normalize(&text).chars().any(char::is_alphanumeric) =>
+            {
+                continue
+            }
+            TokenKind::Filter {
+                key: "fuzzy",
+                value,
+            } => {
+                if token.negated || prev == Some(Prev::Or) {
+                    return Err(error("fuzzy: は - や OR と組み合わせられません"));
+                }
+                fuzzy = match value.trim().to_ascii_lowercase().as_str() {
+                    "off" | "false" | "no" => false,
+                    "on" | "true" | "yes" => true,
+                    _ => {
+                        return Err(QueryError(format!(
+                            "fuzzy: は on か off です（`{value}` は読めません）"
+                        )))
+                    }
+                };
+                filters.push(ActiveFilter {
+                    label: format!("fuzzy:{value}"),
+                    span: token.span,
+                });
+                continue;
+            }
+            TokenKind::Text { text, phrase } => (Clause::Text { text, phrase }, None),
+            TokenKind::Filter { key, value } => {
+                let filter = filter(key, &value, offset)?;
+                (Clause::Filter(filter), Some(format!("{key}:{value}")))
+            }
+        };
+
+        if token.negated {
+            if prev == Some(Prev::Or) {
+                return Err(error(NEGATED_OR));
+            }
+            if let Some(label) = label {
+                filters.push(ActiveFilter {
+                    label: format!("-{label}"),
+                    span: token.span,
+                });
+            }
+            excluded.push(clause);
+            prev = Some(Prev::Negated);
+            continue;
+        }
+
+        match groups.last_mut().filter(|_| prev == Some(Prev::Or)) {
+            Some(group) => {
+                group.clauses.push(clause);
+                group.labels = group.labels.take().zip(label).map(|(mut labels, label)| {
+                    labels.push(label);
+                    labels
+                });
+                group.span.end = token.span.end;
+            }
+            None => groups.push(Group {
+                clauses: vec![clause],
+                labels: label.map(|l| vec![l]),
+                span: token.span,
+            }),
+        }
+        prev = Some(Prev::Positive);
+    }
+    if prev == Some(Prev::Or) {
+        return Err(error("OR の後に語がありません"));
+    }
+    if groups.is_empty() {
+        return Err(error(if excluded.is_empty() {
+            "検索できる文字が含まれていません"
+        } else {
+            "除外（-）する語だけでは検索できません。探す語か絞り込みを 1 つ以上入れてください"
+        }));
+    }
+
+    let bound = |pick: fn(&Filter) -> Option<DateTime<FixedOffset>>| {
+        groups
+            .iter()
+            .filter_map(|g| match g.clauses.as_slice() {
+                [Clause::Filter(f)] => pick(f),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let after = bound(|f| match f {
+        Filter::After(at) => Some(*at),
+        _ => None,
+    });
+    let before = bound(|f| match f {
+        Filter::Before(at) => Some(*at),
+        _ => None,
+    });
+    if let (Some(after), Some(before)) = (after.iter().max(), before.iter().min()) {
+        if after >= before {
+            return Err(error(
+                "after: の日付が before: と同じか後になっているため、当てはまる記事がありません",
+            ));
+        }
+    }
+
+    filters.extend(groups.iter().filter_map(|g| {
+        Some(ActiveFilter {
+            label: g.labels.as_ref()?.join(" OR "),
+            span: g.span.clone(),
+        })
+    }));
+    filters.sort_by_key(|f| f.span.start);
+    Ok(Query {
+        groups: groups.into_iter().map(|g| g.clauses).collect(),
+        excluded,
+        fuzzy,
+        filters,
+    })
+}
+
+const NEGATED_OR: &str = "除外（-）した項は OR でつなげません";
+
+fn error(message: &str) -> QueryError {
+    QueryError(message.to_string())
+}
+
+fn lex(input: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while let Some(c) = input[i..].chars().next() {
+        if c.is_whitespace() {
+            i += c.len_utf8();
+            continue;
+        }
+        let start = i;
+        // 「a - b」のように単独の - は区切りとして読む
+        let negated = c == '-' && input[i + 1..].starts_with(|n: char| !n.is_whitespace());
+        if negated {
+            i += 1;
+        }
+        if input[i..].starts_with('"') {
+            let (text, end) = quoted(input, i)?;
+            i = end;
+            tokens.push(Token {
+                kind: TokenKind::Text { text, phrase: true },
+                negated,
+                span: start..i,
+            });
+            continue;
+        }
+
+        let word_end = input[i..]
+            .find(char::is_whitespace)
+            .map_or(input.len(), |n| i + n);
+        let word = &input[i..word_end];
+        let filter_key = word
+            .split_once(':')
+            .and_then(|(key, _)| FILTER_KEYS.iter().find(|k| k.eq_ignore_ascii_case(key)));
+        let kind = if let Some(&key) = filter_key {
+            // tag:"foo bar" のように値を " で囲んでもよい
+            let value_start = i + key.len() + 1;
+            let (value, end) = if input[value_start..].starts_with('"') {
+                quoted(input, value_start)?
+            } else {
+                (input[value_start..word_end].to_string(), word_end)
+            };
+            i = end;
+            TokenKind::Filter { key, value }
+        } else {
+            i = word_end;
+            if word == "OR" && !negated {
+                TokenKind::Or
+            } else {
+                TokenKind::Text {
+                    text: word.to_string(),
+                    phrase: false,
+                }
+            }
+        };
+        tokens.push(Token {
+            kind,
+            negated,
+            span: start..i,
+        });
+    }
+    Ok(tokens)
+}
+
+/// `input[at..]` の `"..."` を読み、中身（空白はまとめる）と閉じ `"` の後の位置を返す
+fn quoted(input: &str, at: usize) -> Result<(String, usize), QueryError> {
+    let inner = at + 1;
+    let Some(len) = input[inner..].find('"') else {
+        return Err(error("\" が閉じられていません"));
+    };
+    let text = input[inner..inner + len]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    Ok((text, inner + len + 1))
+}
+
+fn filter(key: &str, value: &str, offset: FixedOffset) -> Result<Filter, QueryError> {
+    let value = value.trim();
+    if value.is_empty() {
+        let example = match key {
+            "tag" => "rust",
+            "genre" => "tech",
+            _ => "2024-05-01",
+        };
+        return Err(QueryError(format!(
+            "{key}: の後に値がありません（例: {key}:{example}）"
+        )));
+    }
+    let date = || {
+        day_start(value, offset).ok_or_else(|| {
+            QueryError(format!(
+                "{key}: の日付 `{value}` が読めません（2024, 2024-05, 2024-05-01 のように書きます）"
+            ))
+        })
+    };
+    Ok(match key {
+        "tag" => Filter::Tag(tag_key(value)),
+        "genre" => Filter::Genre(value.to_lowercase()),
+        "before" => Filter::Before(date()?),
+        _ => Filter::After(date()?),
+    })
+}
+
+/// `2024` / `2024-05` / `2024-05-01`（`/` 区切りも可）の初日の 0 時
+fn day_start(value: &str, offset: FixedOffset) -> Option<DateTime<FixedOffset>> {
+    let value = value.replace('/', "-");
+    let date = [
+        value.clone(),
+        format!("{value}-01"),
+        format!("{value}-01-01"),
+    ]
+    .iter()
+    .find_map(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok())?;
+    offset
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jst() -> FixedOffset {
+        FixedOffset::east_opt(9 * 3600).unwrap()
+    }
+
+    fn parse_ok(input: &str) -> Query {
+        match parse(input, jst()) {
+            Ok(query) => query,
+            Err(e) => panic!("`{input}` failed: {e}"),
+        }
+    }
+
+    fn parse_err(input: &str) -> String {
+        match parse(input, jst()) {
+            Ok(_) => panic!("`{input}` should fail"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    fn texts(clauses: &[Clause]) -> Vec<(&str, bool)> {
+        clauses
+            .iter()
+            .filter_map(|c| match c {
+                Clause::Text { text, phrase } => Some((text.as_str(), *phrase)),
+                Clause::Filter(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn or_joins_neighbours_into_one_group() {
+        let query = parse_ok("rust OR go \"型 推論\"");
+        assert_eq!(query.groups.len(), 2);
+        assert_eq!(texts(&query.groups[0]), [("rust", false), ("go", false)]);
+        assert_eq!(texts(&query.groups[1]), [("型 推論", true)]);
+        assert_eq!(query.needles(), ["rust", "go", "型 推論"]);
+    }
+
+    #[test]
+    fn misplaced_or_is_an_error() {
+        assert_eq!(parse_err("OR rust"), "OR の前に語がありません");
+        assert_eq!(parse_err("rust OR"), "OR の後に語がありません");
+        assert_eq!(parse_err("rust OR OR go"), "OR が続いています");
+        // 小文字の or はただの語
+        assert_eq!(parse_ok("rust or go").groups.len(), 3);
+    }
+
+    #[test]
+    fn negation_is_excluded_and_cannot_be_ored() {
+        let query = parse_ok("rust -unsafe");
+        assert_eq!(query.groups.len(), 1);
+        assert_eq!(texts(&query.excluded), [("unsafe", false)]);
+        // 単独の - は区切り
+        assert!(parse_ok("rust - go").excluded.is_empty());
+
+        assert_eq!(parse_err("-unsafe OR rust"), NEGATED_OR);
+        assert_eq!(parse_err("rust OR -unsafe"), NEGATED_OR);
+        assert!(parse_err("-unsafe").starts_with("除外（-）する語だけでは"));
+    }
+
+    #[test]
+    fn quoted_tag_value_keeps_spaces() {
+        let query = parse_ok("tag:\"Rust  Async\" tokio");
+        let [Clause::Filter(Filter::Tag(key))] = query.groups[0].as_slice() else {
+            panic!("expected a tag filter first");
+        };
+        assert_eq!(*key, tag_key("Rust Async"));
+        assert_eq!(query.filters.len(), 1);
+        assert_eq!(query.filters[0].label, "tag:Rust Async");
+        assert_eq!(query.filters[0].span, 0..17);
+        assert_eq!(parse_err("tag:\"rust"), "\" が閉じられていません");
+        assert!(parse_err("tag:").starts_with("tag: の後に値がありません"));
+    }
+
+    #[test]
+    fn after_must_be_before_before() {
+        assert!(parse("after:2024-05 before:2024-06", jst()).is_ok());
+        let message =
+            "after: の日付が before: と同じか後になっているため、当てはまる記事がありません";
+        assert_eq!(parse_err("after:2024-05 before:2024-05-01"), message);
+        assert_eq!(parse_err("after:2025 before:2024/12/31"), message);
+        // OR の中の日付は範囲の判定に使わない
+        assert!(parse("after:2025 before:2024 OR rust", jst()).is_ok());
+        assert!(parse_err("before:2024-13").contains("読めません"));
+    }
+
+    #[test]
+    fn dates_start_at_midnight_in_the_site_zone() {
+        let query = parse_ok("after:2024-05");
+        let [Clause::Filter(Filter::After(at))] = query.groups[0].as_slice() else {
+            panic!("expected an after: filter");
+        };
+        assert_eq!(at.to_rfc3339(), "2024-05-01T00:00:00+09:00");
+    }
+
+    #[test]
+    fn fuzzy_switch_stands_alone() {
+        assert!(parse_ok("rust").fuzzy);
+        let query = parse_ok("rsut fuzzy:off");
+        assert!(!query.fuzzy);
+        assert_eq!(query.filters[0].label, "fuzzy:off");
+        assert!(parse_err("-fuzzy:off rust").starts_with("fuzzy: は"));
+        assert!(parse_err("rust OR fuzzy:off").starts_with("fuzzy: は"));
+        assert!(parse_err("rust fuzzy:maybe").contains("`maybe` は読めません"));
+        // 長音符だけの語は読み飛ばす
+        assert_eq!(parse_ok("rust ー").groups.len(), 1);
+    }
+}
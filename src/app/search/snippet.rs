@@ -0,0 +1,328 @@
+use crate::app::state::{SearchIndexEntry, Section};
+
+use super::{normalize, normalized_chars};
+
+/// 抜き出す範囲の長さ（文字数。2 か所に分けるときはそれぞれ半分）
+const WINDOW: usize = 160;
+/// 最初の一致の前に残す文字数
+const LEAD: usize = 20;
+
+/// `<mark>` 付きの抜粋と、一番よく当たった見出し
+pub struct Snippet<'a> {
+    /// エスケープ済みの HTML
+    pub html: String,
+    pub section: Option<&'a Section>,
+}
+
+/// 本文中の一致 1 つ（位置は元の本文の文字数）
+struct Match {
+    start: usize,
+    end: usize,
+    needle: usize,
+    /// 0 は最初の見出しより前、k は `sections[k - 1]` の中
+    section: usize,
+}
+
+/// 抜き出す範囲（`matches[first..last]` を含む）
+#[derive(Clone, Copy)]
+struct Window {
+    first: usize,
+    last: usize,
+}
+
+/// 検索語が一番まとまって出てくる範囲を抜き出し、一致した語を `<mark>` で囲む
+///
+/// 1 つの見出しの中で、なるべく多くの種類の語を含む範囲を選ぶ。1 か所で全部の語を
+/// 拾えないときは、残りの語を含む 2 か所目を足す。どこにも当たらなければ本文の先頭。
+pub fn snippet<'a>(entry: &'a SearchIndexEntry, needles: &[&str]) -> Snippet<'a> {
+    let chars: Vec<char> = entry.body.chars().collect();
+    let matches = find_matches(entry, needles);
+    let found = {
+        let mut ids: Vec<usize> = matches.iter().map(|m| m.needle).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids.len()
+    };
+    if matches.is_empty() {
+        let end = chars.len().min(WINDOW);
+        return Snippet {
+            html: render(&chars, &[(0, end)], &[]),
+            section: None,
+        };
+    }
+
+    let whole = best_window(&matches, &vec![false; needles.len()], WINDOW, &[]);
+    let windows: Vec<Window> = match whole {
+        Some(w) if distinct(&matches, w) == found => vec![w],
+        _ => {
+            let mut covered = vec![false; needles.len()];
+            let mut halves = Vec::new();
+            if let Some(first) = best_window(&matches, &covered, WINDOW / 2, &[]) {
+                mark_covered(&matches, first, &mut covered);
+                halves.push(first);
+                halves.extend(best_window(&matches, &covered, WINDOW / 2, &halves));
+            }
+            // 2 か所目で増えないなら広い 1 か所のほうがよい
+            if halves.len() > 1 {
+                halves
+            } else {
+                whole.into_iter().collect()
+            }
+        }
+    };
+    let width = if windows.len() > 1 {
+        WINDOW / 2
+    } else {
+        WINDOW
+    };
+    // 見出しは一番よく当たった（最初に選んだ）範囲のもの
+    let section = windows
+        .first()
+        .and_then(|w| matches[w.first].section.checked_sub(1))
+        .map(|k| &entry.sections[k]);
+
+    let mut ranges: Vec<(usize, usize)> = windows
+        .iter()
+        .map(|&w| char_range(entry, &matches, w, width, chars.len()))
+        .collect();
+    ranges.sort_unstable();
+    Snippet {
+        html: render(&chars, &ranges, &matches),
+        section,
+    }
+}
+
+fn find_matches(entry: &SearchIndexEntry, needles: &[&str]) -> Vec<Match> {
+    // 正規化した文字で探し、元の本文の位置に戻す
+    let normalized = normalized_chars(&entry.body);
+    let folded: Vec<char> = normalized.iter().map(|&(c, _)| c).collect();
+    let mut matches = Vec::new();
+    for (needle_id, needle) in needles.iter().enumerate() {
+        let needle: Vec<char> = normalize(needle).chars().collect();
+        if needle.is_empty() {
+            continue;
+        }
+        let mut pos = 0;
+        while pos + needle.len() <= folded.len() {
+            if folded[pos..pos + needle.len()] != needle[..] {
+                pos += 1;
+                continue;
+            }
+            let start = normalized[pos].1;
+            matches.push(Match {
+                start,
+                end: normalized[pos + needle.len() - 1].1 + 1,
+                needle: needle_id,
+                section: entry
+                    .sections
+                    .partition_point(|s| s.start as usize <= start),
+            });
+            pos += needle.len();
+        }
+    }
+    matches.sort_by_key(|m| (m.start, m.end));
+    matches
+}
+
+/// まだ拾っていない語の種類が多く、次に一致の数が多い範囲
+///
+/// 範囲は 1 つの見出しの中に収め、`taken` と重なるものは選ばない。
+fn best_window(
+    matches: &[Match],
+    covered: &[bool],
+    width: usize,
+    taken: &[Window],
+) -> Option<Window> {
+    let mut best: Option<(usize, usize, Window)> = None;
+    for first in 0..matches.len() {
+        let head = &matches[first];
+        let mut last = first;
+        while last < matches.len()
+            && matches[last].section == head.section
+            && matches[last].end <= head.start + width
+        {
+            last += 1;
+        }
+        // 一致した語が width より長いときもその語だけの範囲にする
+        let window = Window {
+            first,
+            last: last.max(first + 1),
+        };
+        if taken.iter().any(|t| overlaps(matches, *t, window)) {
+            continue;
+        }
+        let mut seen = vec![false; covered.len()];
+        let mut new = 0;
+        for m in &matches[window.first..window.last] {
+            if !covered[m.needle] && !seen[m.needle] {
+                seen[m.needle] = true;
+                new += 1;
+            }
+        }
+        let count = window.last - window.first;
+        if new > 0 && best.is_none_or(|(n, c, _)| (new, count) > (n, c)) {
+            best = Some((new, count, window));
+        }
+    }
+    best.map(|(_, _, window)| window)
+}
+
+fn distinct(matches: &[Match], window: Window) -> usize {
+    let mut ids: Vec<usize> = matches[window.first..window.last]
+        .iter()
+        .map(|m| m.needle)
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids.len()
+}
+
+fn mark_covered(matches: &[Match], window: Window, covered: &mut [bool]) {
+    for m in &matches[window.first..window.last] {
+        covered[m.needle] = true;
+    }
+}
+
+fn overlaps(matches: &[Match], a: Window, b: Window) -> bool {
+    let span = |w: Window| (matches[w.first].start, matches[w.last - 1].end);
+    let (a_start, a_end) = span(a);
+    let (b_start, b_end) = span(b);
+    a_start < b_end && b_start < a_end
+}
+
+/// 範囲を文字の位置にする（少し前から始め、見出しの区切りははみ出さない）
+fn char_range(
+    entry: &SearchIndexEntry,
+    matches: &[Match],
+    window: Window,
+    width: usize,
+    len: usize,
+) -> (usize, usize) {
+    let k = matches[window.first].section;
+    let section_start = k
+        .checked_sub(1)
+        .map_or(0, |i| entry.sections[i].start as usize);
+    let section_end = entry.sections.get(k).map_or(len, |s| s.start as usize);
+    let first = matches[window.first].start;
+    let last_end = matches[window.first..window.last]
+        .iter()
+        .map(|m| m.end)
+        .max()
+        .unwrap_or(first);
+    let start = first.saturating_sub(LEAD).max(section_start);
+    let end = (start + width).min(section_end).max(last_end).min(len);
+    (start, end)
+}
+
+/// 範囲をつないで HTML にする（範囲の外を省いたところは `…`）
+fn render(chars: &[char], ranges: &[(usize, usize)], matches: &[Match]) -> String {
+    let mut html = String::new();
+    let mut prev_end = 0;
+    for &(start, end) in ranges {
+        // 前の範囲と重なった分は出さない
+        let start = start.max(prev_end);
+        if start >= end {
+            continue;
+        }
+        if start > prev_end {
+            html.push('…');
+        }
+        let mut pos = start;
+        for m in matches.iter().filter(|m| m.start < end && m.end > start) {
+            // 重なった一致は先に始まったものにまとめる
+            let mark_start = m.start.max(pos);
+            if mark_start >= m.end {
+                continue;
+            }
+            escape_into(&mut html, &chars[pos..mark_start]);
+            html.push_str("<mark>");
+            escape_into(&mut html, &chars[mark_start..m.end.min(end)]);
+            html.push_str("</mark>");
+            pos = m.end.min(end);
+        }
+        escape_into(&mut html, &chars[pos..end]);
+        prev_end = end;
+    }
+    if prev_end < chars.len() {
+        html.push('…');
+    }
+    html
+}
+
+fn escape_into(out: &mut String, text: &[char]) {
+    for &c in text {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(body: &str) -> SearchIndexEntry {
+        SearchIndexEntry {
+            slug: "post".to_string(),
+            title: "post".to_string(),
+            published_at: None,
+            updated_at: None,
+            description: None,
+            tags: Vec::new(),
+            genre: None,
+            body: body.into(),
+            sections: Box::new([]),
+            listed: true,
+            publish_at: None,
+        }
+    }
+
+    fn html(body: &str, needles: &[&str]) -> String {
+        snippet(&entry(body), needles).html
+    }
+
+    #[test]
+    fn escapes_text_around_marks() {
+        assert_eq!(
+            html("a <b> & \"c\" 'd'", &["b"]),
+            "a &lt;<mark>b</mark>&gt; &amp; &quot;c&quot; &#39;d&#39;"
+        );
+        assert_eq!(
+            html("<mark>x</mark>", &["x"]),
+            "&lt;mark&gt;<mark>x</mark>&lt;/mark&gt;"
+        );
+    }
+
+    #[test]
+    fn overlapping_matches_do_not_repeat_text() {
+        assert_eq!(
+            html("abcd", &["abc", "bcd"]),
+            "<mark>abc</mark><mark>d</mark>"
+        );
+        assert_eq!(html("abcd", &["abcd", "bc"]), "<mark>abcd</mark>");
+    }
+
+    #[test]
+    fn marks_the_original_spelling() {
+        assert_eq!(
+            html("サーバーの設定", &["さば"]),
+            "<mark>サーバ</mark>ーの設定"
+        );
+        assert_eq!(html("ＲＵＳＴ入門", &["rust"]), "<mark>ＲＵＳＴ</mark>入門");
+    }
+
+    #[test]
+    fn long_bodies_are_cut_around_the_match() {
+        let body = format!("{}needle{}", "あ".repeat(300), "い".repeat(300));
+        let out = html(&body, &["needle"]);
+        assert!(out.starts_with('…') && out.ends_with('…'));
+        assert!(out.contains(&format!("{}<mark>needle</mark>", "あ".repeat(LEAD))));
+        // 当たらなければ先頭から
+        assert!(html(&body, &["nothing"]).starts_with(&"あ".repeat(WINDOW)));
+    }
+}
@@ -1,24 +1,46 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::PathBuf,
     sync::{Arc, LazyLock},
+    time::Duration,
 };
 
+use chrono::{DateTime, FixedOffset, Utc};
 use futures::{stream, StreamExt, TryStreamExt};
 use regex::Regex;
 use tokio::fs;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 
-use crate::frontmatter::FrontMatter;
+use crate::{
+    cards::{post_cards_html, POSTS_LIST_TOKEN},
+    feed,
+    frontmatter::{tag_key, FrontMatter, PRIVATE_DIR},
+    sitemap,
+};
 
 use super::{
-    markdown_enabled,
+    config, live_reload, markdown_enabled,
     render::{
         prerender_blog_page, prerender_profile_page, prerender_static_page, prerender_top_page,
+        BlogListItem, PostNav, SeriesLink, SeriesNav,
     },
+    search::SearchIndex,
+    SITEMAP_PATH,
 };
 
+/// 記事の下に出す関連記事の数
+const RELATED_POSTS_LIMIT: usize = 3;
+
 static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new("<[^>]+>").expect("valid regex"));
+/// ビルド時に id とパーマリンクを付けた見出し（build/headings.rs）
+static HEADING_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<h([2-4])((?:\s[^>]*)?)>(.*?)</h[2-4]>").expect("valid regex")
+});
+static ID_ATTR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\bid\s*=\s*"([^"]*)""#).expect("valid regex"));
+static HEADING_ANCHOR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)<a class="heading-anchor"[^>]*>.*?</a>"#).expect("valid regex")
+});
 
 #[derive(Clone)]
 pub struct AppState {
@@ -26,9 +48,26 @@ pub struct AppState {
     pub(crate) prerender_profile: Arc<str>,
     pub(crate) prerender_pgp: Arc<str>,
     pub(crate) blog_pages: Arc<HashMap<String, Arc<str>>>,
+    /// 下書きのプレビュー用ページ（/preview/{slug} 専用）
+    pub(crate) preview_pages: Arc<HashMap<String, Arc<str>>>,
     pub(crate) blog_markdowns: Arc<HashMap<String, Arc<str>>>,
     pub(crate) blog_typs: Arc<HashMap<String, Arc<str>>>,
-    pub(crate) search_index: Arc<Vec<SearchIndexEntry>>,
+    /// PDF 版のパス（大きいのでメモリには載せない）
+    pub(crate) blog_pdfs: Arc<HashMap<String, PathBuf>>,
+    pub(crate) search_index: Arc<SearchIndex>,
+    /// 予約投稿の公開時刻（slug -> publish_at）
+    pub(crate) scheduled: Arc<HashMap<String, DateTime<FixedOffset>>>,
+    /// 予約投稿の生成物（static からのパス -> slug）。公開前は /assets でも返さない
+    pub(crate) scheduled_files: Arc<HashMap<String, String>>,
+    /// 連載 id -> 記事の slug（series_order 順）
+    pub(crate) series: Arc<HashMap<String, Vec<String>>>,
+}
+
+impl AppState {
+    /// 予約投稿で公開時刻より前なら false
+    pub(crate) fn is_published(&self, slug: &str, now: DateTime<Utc>) -> bool {
+        self.scheduled.get(slug).is_none_or(|t| *t <= now)
+    }
 }
 
 pub type SharedAppState = Arc<RwLock<AppState>>;
@@ -41,10 +80,29 @@ pub struct SearchIndexEntry {
     pub updated_at: Option<String>,
     pub description: Option<String>,
     pub tags: Vec<String>,
-    pub title_lc: String,
-    pub body_lc: String,
-    pub body_chars: Arc<[char]>,
-    pub body_lower: Arc<[char]>,
+    pub genre: Option<String>,
+    /// HTML を外した本文（スニペットの元。索引は語と位置だけなので、本文はここにだけ持つ）
+    pub body: Box<str>,
+    /// 本文中の見出し（出てくる順）
+    pub sections: Box<[Section]>,
+    pub listed: bool,
+    pub publish_at: Option<DateTime<FixedOffset>>,
+}
+
+/// 本文中の h2–h4 の見出し
+#[derive(Clone)]
+pub struct Section {
+    /// `SearchIndexEntry::body` の何文字目から（見出しの文字列そのものから始まる）
+    pub start: u32,
+    pub id: Box<str>,
+    pub title: Box<str>,
+}
+
+impl SearchIndexEntry {
+    /// 一覧・検索・タグページに出してよいか
+    pub fn is_listed_at(&self, now: DateTime<Utc>) -> bool {
+        self.listed && self.publish_at.is_none_or(|t| t <= now)
+    }
 }
 
 pub async fn build_prerendered_state() -> anyhow::Result<AppState> {
@@ -55,68 +113,102 @@ pub async fn build_prerendered_state() -> anyhow::Result<AppState> {
     let profile_meta_path = base.join("profile_meta.json");
     let pgp_path = base.join("pgp.html");
     let pgp_meta_path = base.join("pgp_meta.json");
+    let drafts_path = PathBuf::from(PRIVATE_DIR).join("drafts.json");
 
     let index_bytes = fs::read(&meta_path).await?;
     let metas: Vec<FrontMatter> = serde_json::from_slice(&index_bytes)?;
+    let now = Utc::now();
+    // 一覧に出すかはビルド時ではなく今の時刻で決める（予約投稿の公開で変わる）
+    let listed: Vec<FrontMatter> = metas
+        .iter()
+        .filter(|m| m.is_listed_at(now))
+        .cloned()
+        .collect();
+    let has_scheduled = metas.iter().any(|m| m.publish_time().is_some());
+    let series = group_series(&metas);
+    let mut series_navs = build_series_navs(&metas, &series, now);
 
-    let results: Vec<_> = stream::iter(metas.into_iter())
+    // 関連記事で本文を比べるので先に全部読んでおく
+    let pages: Vec<(FrontMatter, String)> = stream::iter(metas)
         .map(|meta| async move {
-            let slug = meta.slug.clone();
-            let html_path = PathBuf::from("static").join(&meta.html);
-            let html_content = fs::read_to_string(&html_path).await?;
-            let prerendered = Arc::<str>::from(prerender_blog_page(&meta, &html_content));
-
-            let typ_src = {
-                let typ_path = PathBuf::from("content").join(format!("{slug}.typ"));
-                match fs::read_to_string(&typ_path).await {
-                    Ok(src) => Some(Arc::<str>::from(src)),
-                    Err(_) => None,
-                }
-            };
+            let html_content = fs::read_to_string(meta.html_file()).await?;
+            anyhow::Ok((meta, html_content))
+        })
+        .buffered(8)
+        .try_collect()
+        .await?;
+    let mut post_navs = build_post_navs(&pages, now, config().server.related_text_similarity);
+    let scheduled_files = scheduled_files(&pages);
+    let blog_pdfs: HashMap<String, PathBuf> = pages
+        .iter()
+        .filter_map(|(meta, _)| {
+            let pdf = meta.pdf.as_ref()?;
+            Some((meta.slug.clone(), PathBuf::from("static").join(pdf)))
+        })
+        .collect();
+
+    let results: Vec<_> = stream::iter(pages.into_iter())
+        .map(|(meta, html_content)| {
+            let series_nav = series_navs.remove(&meta.slug);
+            let post_nav = post_navs.remove(&meta.slug);
+            async move {
+                let slug = meta.slug.clone();
+                let prerendered = Arc::<str>::from(prerender_blog_page(
+                    &meta,
+                    &html_content,
+                    series_nav.as_ref(),
+                    post_nav.as_ref(),
+                ));
 
-            let markdown = if markdown_enabled() {
-                if let Some(md_rel) = meta.markdown.as_ref() {
-                    let md_path = PathBuf::from("static").join(md_rel);
-                    match fs::read_to_string(&md_path).await {
-                        Ok(md_content) => Some(Arc::<str>::from(md_content)),
+                let typ_src = {
+                    // 入れ子やバンドルの記事はビルド時に記録したパスから読む
+                    let typ_path =
+                        meta.source.as_ref().map(PathBuf::from).unwrap_or_else(|| {
+                            PathBuf::from("content").join(format!("{slug}.typ"))
+                        });
+                    match fs::read_to_string(&typ_path).await {
+                        Ok(src) => Some(Arc::<str>::from(src)),
                         Err(_) => None,
                     }
+                };
+
+                let markdown = if markdown_enabled() {
+                    if let Some(md_rel) = meta.markdown.as_ref() {
+                        let md_path = PathBuf::from("static").join(md_rel);
+                        match fs::read_to_string(&md_path).await {
+                            Ok(md_content) => Some(Arc::<str>::from(md_content)),
+                            Err(_) => None,
+                        }
+                    } else {
+                        None
+                    }
                 } else {
                     None
-                }
-            } else {
-                None
-            };
-
-            let plain = html_to_plain(&html_content);
-            let body_chars: Arc<[char]> = plain.chars().collect::<Vec<_>>().into();
-            let body_lower_str = plain.to_lowercase();
-            let body_lower: Arc<[char]> = body_lower_str.chars().collect::<Vec<_>>().into();
-
-            let description = meta
-                .meta
-                .get("description")
-                .or_else(|| meta.meta.get("og:description"))
-                .cloned();
-
-            let search_entry = SearchIndexEntry {
-                slug: slug.clone(),
-                title: meta.title.clone().unwrap_or_else(|| "Untitled".to_string()),
-                published_at: meta.published_at.clone(),
-                updated_at: meta.updated_at.clone(),
-                description,
-                tags: meta.tags.clone(),
-                title_lc: meta
-                    .title
-                    .as_deref()
-                    .map(|s| s.to_lowercase())
-                    .unwrap_or_default(),
-                body_lc: body_lower_str,
-                body_chars,
-                body_lower,
-            };
-
-            anyhow::Ok((slug, prerendered, typ_src, markdown, search_entry))
+                };
+
+                let description = meta
+                    .meta
+                    .get("description")
+                    .or_else(|| meta.meta.get("og:description"))
+                    .cloned();
+                let (body, sections) = html_to_sections(&html_content);
+
+                let search_entry = SearchIndexEntry {
+                    slug: slug.clone(),
+                    title: meta.title.clone().unwrap_or_else(|| "Untitled".to_string()),
+                    published_at: meta.published_at.clone(),
+                    updated_at: meta.updated_at.clone(),
+                    description,
+                    tags: meta.tags.clone(),
+                    genre: meta.genre.clone(),
+                    body: body.into_boxed_str(),
+                    sections: sections.into_boxed_slice(),
+                    listed: meta.is_listed(),
+                    publish_at: meta.publish_time(),
+                };
+
+                anyhow::Ok((slug, prerendered, typ_src, markdown, search_entry))
+            }
         })
         .buffer_unordered(8)
         .try_collect()
@@ -126,7 +218,11 @@ pub async fn build_prerendered_state() -> anyhow::Result<AppState> {
     let mut blog_markdowns = HashMap::new();
     let mut blog_typs = HashMap::new();
     let mut search_entries = Vec::new();
+    let mut scheduled = HashMap::new();
     for (slug, prerendered, typ_src, markdown, search_entry) in results {
+        if let Some(publish_at) = search_entry.publish_at {
+            scheduled.insert(slug.clone(), publish_at);
+        }
         blog_pages.insert(slug.clone(), prerendered);
         if let Some(src) = typ_src {
             blog_typs.insert(slug.clone(), src);
@@ -136,8 +232,33 @@ pub async fn build_prerendered_state() -> anyhow::Result<AppState> {
         }
         search_entries.push(search_entry);
     }
+    // 読み込みの完了順にならないよう、新しい順にそろえてから索引を作る
+    search_entries.sort_by(|a, b| {
+        b.published_at
+            .cmp(&a.published_at)
+            .then_with(|| a.slug.cmp(&b.slug))
+    });
 
-    let home_html = fs::read_to_string(&home_path).await.unwrap_or_default();
+    // 下書きは検索にも一覧にも入れず、プレビュー用にだけ描画しておく
+    let drafts: Vec<FrontMatter> = fs::read(&drafts_path)
+        .await
+        .ok()
+        .and_then(|b| serde_json::from_slice(&b).ok())
+        .unwrap_or_default();
+    let mut preview_pages = HashMap::new();
+    for meta in drafts {
+        if let Ok(html_content) = fs::read_to_string(meta.html_file()).await {
+            preview_pages.insert(
+                meta.slug.clone(),
+                Arc::<str>::from(prerender_blog_page(&meta, &html_content, None, None)),
+            );
+        }
+    }
+
+    let home_html = fs::read_to_string(&home_path)
+        .await
+        .unwrap_or_default()
+        .replace(POSTS_LIST_TOKEN, &post_cards_html(&listed));
     let top = Arc::<str>::from(prerender_top_page(&home_html));
     let profile_html = fs::read_to_string(&profile_path).await.unwrap_or_default();
     let profile_meta: FrontMatter = fs::read_to_string(&profile_meta_path)
@@ -149,15 +270,19 @@ pub async fn build_prerendered_state() -> anyhow::Result<AppState> {
             ..Default::default()
         });
     let profile = Arc::<str>::from(prerender_profile_page(&profile_meta, &profile_html));
-    let pgp_meta: FrontMatter = fs::read_to_string(&pgp_meta_path)
+    let pgp_meta: Option<FrontMatter> = fs::read_to_string(&pgp_meta_path)
         .await
         .ok()
-        .and_then(|s| serde_json::from_str(&s).ok())
-        .unwrap_or_else(|| FrontMatter {
-            title: Some("PGP 公開鍵".to_string()),
-            slug: "pgp".to_string(),
-            ..Default::default()
-        });
+        .and_then(|s| serde_json::from_str(&s).ok());
+    if has_scheduled {
+        let pgp_meta = pgp_meta.clone();
+        tokio::task::spawn_blocking(move || write_listings(&listed, pgp_meta.as_ref())).await??;
+    }
+    let pgp_meta = pgp_meta.unwrap_or_else(|| FrontMatter {
+        title: Some("PGP 公開鍵".to_string()),
+        slug: "pgp".to_string(),
+        ..Default::default()
+    });
     let pgp_html = fs::read_to_string(&pgp_path).await.unwrap_or_default();
     let pgp = Arc::<str>::from(prerender_static_page(
         &pgp_meta,
@@ -171,26 +296,353 @@ pub async fn build_prerendered_state() -> anyhow::Result<AppState> {
         prerender_profile: profile,
         prerender_pgp: pgp,
         blog_pages: Arc::new(blog_pages),
+        preview_pages: Arc::new(preview_pages),
         blog_markdowns: Arc::new(blog_markdowns),
         blog_typs: Arc::new(blog_typs),
-        search_index: Arc::new(search_entries),
+        blog_pdfs: Arc::new(blog_pdfs),
+        search_index: Arc::new(SearchIndex::build(search_entries)),
+        scheduled: Arc::new(scheduled),
+        scheduled_files: Arc::new(scheduled_files),
+        series: Arc::new(series),
     })
 }
 
+/// ビルドが書いたサイトマップとフィードを今公開されている記事で書き直す
+/// （ビルド時に公開前だった予約投稿を載せるため）
+fn write_listings(listed: &[FrontMatter], pgp_meta: Option<&FrontMatter>) -> anyhow::Result<()> {
+    let site = &config().site;
+    let urls = sitemap::write_sitemap(listed, pgp_meta, &site.url, SITEMAP_PATH)?;
+    let (entries, tags) = feed::write_feeds(listed, site, "static/generated")?;
+    tracing::info!("regenerated sitemap ({urls} urls) and feeds ({entries} entries, {tags} tags)");
+    Ok(())
+}
+
+/// 予約投稿の HTML・Markdown・PDF・OG 画像（バンドルのアセットは handlers 側で見る）
+fn scheduled_files(pages: &[(FrontMatter, String)]) -> HashMap<String, String> {
+    pages
+        .iter()
+        .map(|(meta, _)| meta)
+        .filter(|meta| meta.publish_time().is_some())
+        .flat_map(|meta| {
+            let og = meta
+                .og_image
+                .as_deref()
+                .and_then(|url| url.strip_prefix("/assets/"));
+            [
+                Some(meta.html.as_str()),
+                meta.markdown.as_deref(),
+                meta.pdf.as_deref(),
+                og,
+            ]
+            .into_iter()
+            .flatten()
+            .map(|path| (path.to_string(), meta.slug.clone()))
+        })
+        .collect()
+}
+
+/// 一覧に出す記事を連載ごとにまとめる（series_order → 公開日 → slug の順）
+fn group_series(metas: &[FrontMatter]) -> HashMap<String, Vec<String>> {
+    let mut groups: HashMap<String, Vec<&FrontMatter>> = HashMap::new();
+    for meta in metas.iter().filter(|m| m.is_listed()) {
+        if let Some(id) = meta.series.as_deref() {
+            groups.entry(id.trim().to_string()).or_default().push(meta);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(id, mut parts)| {
+            parts.sort_by(|a, b| {
+                // 番号なしは後ろ
+                let order = |m: &FrontMatter| m.series_order.unwrap_or(u32::MAX);
+                order(a)
+                    .cmp(&order(b))
+                    .then_with(|| a.published_at.cmp(&b.published_at))
+                    .then_with(|| a.slug.cmp(&b.slug))
+            });
+            (id, parts.into_iter().map(|m| m.slug.clone()).collect())
+        })
+        .collect()
+}
+
+/// 公開済みの回だけで「第 N 回」と前後の記事を決める
+fn build_series_navs(
+    metas: &[FrontMatter],
+    series: &HashMap<String, Vec<String>>,
+    now: DateTime<Utc>,
+) -> HashMap<String, SeriesNav> {
+    let by_slug: HashMap<&str, &FrontMatter> = metas.iter().map(|m| (m.slug.as_str(), m)).collect();
+    let link = |m: &FrontMatter| SeriesLink {
+        slug: m.slug.clone(),
+        title: m.title.clone().unwrap_or_else(|| m.slug.clone()),
+    };
+
+    let mut navs = HashMap::new();
+    for (id, slugs) in series {
+        let parts: Vec<&FrontMatter> = slugs
+            .iter()
+            .filter_map(|s| by_slug.get(s.as_str()).copied())
+            .filter(|m| m.publish_time().is_none_or(|t| t <= now))
+            .collect();
+        for (idx, part) in parts.iter().enumerate() {
+            navs.insert(
+                part.slug.clone(),
+                SeriesNav {
+                    id: id.clone(),
+                    position: idx + 1,
+                    total: parts.len(),
+                    prev: idx.checked_sub(1).map(|i| link(parts[i])),
+                    next: parts.get(idx + 1).map(|m| link(m)),
+                },
+            );
+        }
+    }
+    navs
+}
+
 pub async fn build_shared_state() -> anyhow::Result<SharedAppState> {
     let state = build_prerendered_state().await?;
-    Ok(Arc::new(RwLock::new(state)))
+    let shared = Arc::new(RwLock::new(state));
+    tokio::spawn(rebuild_on_publish(shared.clone()));
+    Ok(shared)
+}
+
+/// 状態が差し替わったことを予約公開の待ち合わせに知らせる
+static STATE_SWAPPED: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// 予約投稿の公開時刻に状態を組み直す
+///
+/// 連載や前後の記事のリンクは構築時の公開状況で固まるため、公開された記事が
+/// 次の再構築まで現れない。次の `publish_at` まで眠り、その時点で読み直す。
+async fn rebuild_on_publish(shared: SharedAppState) {
+    loop {
+        let next = {
+            let state = shared.read().await;
+            let now = Utc::now();
+            state
+                .scheduled
+                .values()
+                .filter(|t| **t > now)
+                .min()
+                .copied()
+        };
+        // 公開時刻ちょうどだと is_published の判定とずれうるので少し待つ
+        let wait = next.map(|t| {
+            (t.with_timezone(&Utc) - Utc::now())
+                .to_std()
+                .unwrap_or_default()
+                + Duration::from_secs(1)
+        });
+        match wait {
+            Some(wait) => {
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {
+                        if let Err(e) = reload_state(&shared).await {
+                            eprintln!("scheduled reload failed: {e:?}");
+                        }
+                    }
+                    // 手動の再読み込みで予約が変わったら待ち直す
+                    _ = STATE_SWAPPED.notified() => {}
+                }
+            }
+            None => STATE_SWAPPED.notified().await,
+        }
+    }
 }
 
 pub async fn reload_state(shared: &SharedAppState) -> anyhow::Result<()> {
     // 先に新しい状態を構築してから書き換えることで、ロック時間を最小化する
-    let next = build_prerendered_state().await?;
-    let mut guard = shared.write().await;
-    *guard = next;
-    Ok(())
+    let result = match build_prerendered_state().await {
+        Ok(next) => {
+            *shared.write().await = next;
+            STATE_SWAPPED.notify_one();
+            Ok(())
+        }
+        Err(e) => Err(e),
+    };
+    // 書き換えた後に知らせる（先に読み直されると古いページが返る）
+    live_reload::publish(&result);
+    result
+}
+
+/// 時系列の前後の記事と、タグ・ジャンル（必要なら本文）が近い記事
+///
+/// 一覧に出る公開済みの記事だけが対象。限定公開の記事にも関連記事は出すが、
+/// 前後のリンクは付けない。
+fn build_post_navs(
+    pages: &[(FrontMatter, String)],
+    now: DateTime<Utc>,
+    use_text: bool,
+) -> HashMap<String, PostNav> {
+    let visible = |m: &FrontMatter| m.publish_time().is_none_or(|t| t <= now);
+    let mut listed: Vec<&FrontMatter> = pages
+        .iter()
+        .map(|(m, _)| m)
+        .filter(|m| m.is_listed() && visible(m))
+        .collect();
+    listed.sort_by(|a, b| {
+        a.published_at
+            .cmp(&b.published_at)
+            .then_with(|| a.slug.cmp(&b.slug))
+    });
+    let position: HashMap<&str, usize> = listed
+        .iter()
+        .enumerate()
+        .map(|(i, m)| (m.slug.as_str(), i))
+        .collect();
+    let bigrams: HashMap<&str, HashSet<(char, char)>> = if use_text {
+        pages
+            .iter()
+            .filter(|(m, _)| visible(m))
+            .map(|(m, html)| (m.slug.as_str(), text_bigrams(&html_to_plain(html))))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+    // タグはタグページと同じく tag_key で比べる（同じ記事の表記違いは 1 つに数える）
+    let tag_keys: HashMap<&str, HashSet<String>> = pages
+        .iter()
+        .filter(|(m, _)| visible(m))
+        .map(|(m, _)| {
+            let keys = m
+                .tags
+                .iter()
+                .map(|t| tag_key(t))
+                .filter(|k| !k.is_empty())
+                .collect();
+            (m.slug.as_str(), keys)
+        })
+        .collect();
+
+    let mut navs = HashMap::new();
+    for (meta, _) in pages.iter().filter(|(m, _)| visible(m)) {
+        let own_tags = &tag_keys[meta.slug.as_str()];
+        let idx = position.get(meta.slug.as_str()).copied();
+        let prev = idx
+            .and_then(|i| i.checked_sub(1))
+            .map(|i| BlogListItem::from(listed[i]));
+        let next = idx
+            .and_then(|i| listed.get(i + 1))
+            .map(|m| BlogListItem::from(*m));
+
+        let mut scored: Vec<(f64, &FrontMatter)> = listed
+            .iter()
+            .filter(|other| other.slug != meta.slug)
+            .map(|other| {
+                let mut score = own_tags
+                    .intersection(&tag_keys[other.slug.as_str()])
+                    .count() as f64
+                    * 3.0;
+                if meta.genre.is_some() && meta.genre == other.genre {
+                    score += 2.0;
+                }
+                if let (Some(a), Some(b)) = (
+                    bigrams.get(meta.slug.as_str()),
+                    bigrams.get(other.slug.as_str()),
+                ) {
+                    score += jaccard(a, b) * 10.0;
+                }
+                (score, *other)
+            })
+            .filter(|(score, _)| *score > 0.0)
+            .collect();
+        // 同点なら新しい記事を優先
+        scored.sort_by(|a, b| {
+            b.0.total_cmp(&a.0)
+                .then_with(|| b.1.published_at.cmp(&a.1.published_at))
+        });
+        let related = scored
+            .into_iter()
+            .take(RELATED_POSTS_LIMIT)
+            .map(|(_, m)| BlogListItem::from(m))
+            .collect();
+
+        navs.insert(
+            meta.slug.clone(),
+            PostNav {
+                prev,
+                next,
+                related,
+            },
+        );
+    }
+    navs
+}
+
+/// 日本語でも効くように文字 bigram で比べる
+fn text_bigrams(text: &str) -> HashSet<(char, char)> {
+    let chars: Vec<char> = text
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| c.is_alphanumeric())
+        .collect();
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+fn jaccard(a: &HashSet<(char, char)>, b: &HashSet<(char, char)>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
 }
 
 fn html_to_plain(html: &str) -> String {
     let text = TAG_RE.replace_all(html, " ");
     text.split_whitespace().collect::<Vec<_>>().join(" ")
 }
+
+/// 検索用の本文（タグを外して文字参照を戻したもの）と見出しの位置
+fn html_to_sections(html: &str) -> (String, Vec<Section>) {
+    let html = HEADING_ANCHOR_RE.replace_all(html, "");
+    let mut body = String::new();
+    let mut len = 0;
+    // 追加した文字列が何文字目から始まるか（空なら None）
+    let mut append = |fragment: &str| {
+        let text = decode_entities(&html_to_plain(fragment));
+        if text.is_empty() {
+            return None;
+        }
+        if !body.is_empty() {
+            body.push(' ');
+            len += 1;
+        }
+        let start = len;
+        body.push_str(&text);
+        len += text.chars().count();
+        Some(start as u32)
+    };
+
+    let mut sections = Vec::new();
+    let mut last = 0;
+    for caps in HEADING_RE.captures_iter(&html) {
+        let (Some(whole), Some(attrs), Some(inner)) = (caps.get(0), caps.get(2), caps.get(3))
+        else {
+            continue;
+        };
+        append(&html[last..whole.start()]);
+        last = whole.end();
+        let Some(start) = append(inner.as_str()) else {
+            continue;
+        };
+        if let Some(id) = ID_ATTR_RE.captures(attrs.as_str()) {
+            sections.push(Section {
+                start,
+                id: id[1].into(),
+                title: decode_entities(&html_to_plain(inner.as_str())).into(),
+            });
+        }
+    }
+    append(&html[last..]);
+    (body, sections)
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
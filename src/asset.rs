@@ -1,5 +1,7 @@
 use std::{collections::HashMap, fs, path::Path, sync::LazyLock};
 
+use crate::images::{ImageManifest, ResponsiveImage, IMAGE_MANIFEST_PATH};
+
 static MANIFEST: LazyLock<HashMap<String, String>> = LazyLock::new(|| {
     let path = Path::new("static/generated/assets-manifest.json");
     if let Ok(s) = fs::read_to_string(path) {
@@ -10,6 +12,13 @@ static MANIFEST: LazyLock<HashMap<String, String>> = LazyLock::new(|| {
     HashMap::new()
 });
 
+static IMAGES: LazyLock<ImageManifest> = LazyLock::new(|| {
+    fs::read(IMAGE_MANIFEST_PATH)
+        .ok()
+        .and_then(|b| serde_json::from_slice(&b).ok())
+        .unwrap_or_default()
+});
+
 /// Resolve an asset path using generated manifest. If manifest missing or key not found,
 /// returns the original `path`.
 pub fn asset_url(path: &str) -> String {
@@ -18,3 +27,8 @@ pub fn asset_url(path: &str) -> String {
         .cloned()
         .unwrap_or_else(|| path.to_string())
 }
+
+/// Generated variants for an image under `static/images` (e.g. `/assets/images/foo.jpg`).
+pub fn responsive_image(url: &str) -> Option<ResponsiveImage> {
+    IMAGES.get(url).cloned()
+}
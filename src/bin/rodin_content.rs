@@ -1,38 +1,89 @@
 use anyhow::Result;
+use config::{Site, SiteConfig};
+use diagnostics::DiagnosticFormat;
+use frontmatter::FrontMatter;
 use std::io::{Read, Write};
 use std::net::TcpStream;
 use std::time::Duration;
 
+#[path = "../../build/cache.rs"]
+mod cache;
+#[path = "../../src/cards.rs"]
+mod cards;
+#[path = "rodin_content/check.rs"]
+mod check;
+#[path = "../../src/config.rs"]
+mod config;
+#[path = "../../build/content.rs"]
+mod content;
+#[path = "../../build/diagnostics.rs"]
+mod diagnostics;
+#[path = "../../build/feed.rs"]
+mod feed;
 #[path = "../../src/frontmatter.rs"]
 mod frontmatter;
+#[path = "../../build/gfm.rs"]
+mod gfm;
+#[path = "../../build/headings.rs"]
+mod headings;
+#[path = "../../src/images.rs"]
+mod images;
 #[path = "../../build/markdown.rs"]
 mod markdown;
+#[path = "../../build/og.rs"]
+mod og;
 #[path = "../../build/posts.rs"]
 mod posts;
+#[path = "../../src/preview.rs"]
+mod preview;
+#[path = "../../build/resolver.rs"]
+mod resolver;
+#[path = "../../build/responsive.rs"]
+mod responsive;
+#[path = "rodin_content/scaffold.rs"]
+mod scaffold;
 #[path = "../../build/sitemap.rs"]
 mod sitemap;
+#[path = "rodin_content/watch.rs"]
+mod watch;
 
 const PREAMBLE_PATH: &str = "static/preamble.typ";
 const GENERATED_DIR: &str = "static/generated";
 const GENERATED_MD_DIR: &str = "static/generated/md";
-const PANDOC_FILTER: &str = "scripts/pandoc/html-to-md.lua";
-const DEFAULT_SITE_URL: &str = "https://suzuneu.com";
 const DEFAULT_SITEMAP_PATH: &str = "static/generated/sitemap.xml";
 const DEFAULT_RELOAD_URL: &str = "http://127.0.0.1:3000/__admin/reload";
+const DEFAULT_PREVIEW_TTL_HOURS: i64 = 72;
 
 fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("preview-token") {
+        return print_preview_url(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("check") {
+        return check::run(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("new") {
+        return scaffold::run(&args[1..]);
+    }
+
+    let mut watch_mode = false;
     let mut skip_markdown = false;
-    let mut site_url = DEFAULT_SITE_URL.to_string();
+    let mut site_url: Option<String> = None;
     let mut do_reload = false;
     let mut reload_url: Option<String> = None;
     let mut reload_token: Option<String> = None;
+    let mut deny_warnings = false;
+    let mut diagnostic_format = DiagnosticFormat::Human;
 
-    for arg in std::env::args().skip(1) {
+    for arg in args {
         match arg.as_str() {
             "-h" | "--help" => {
                 print_help();
                 return Ok(());
             }
+            "watch" => {
+                watch_mode = true;
+            }
             "--skip-markdown" | "--no-markdown" | "--no-md" => {
                 skip_markdown = true;
             }
@@ -47,7 +98,17 @@ fn main() -> Result<()> {
                 reload_token = Some(arg.trim_start_matches("--reload-token=").to_string());
             }
             _ if arg.starts_with("--site=") => {
-                site_url = arg.trim_start_matches("--site=").to_string();
+                site_url = Some(arg.trim_start_matches("--site=").to_string());
+            }
+            "--deny-warnings" => {
+                deny_warnings = true;
+            }
+            _ if arg.starts_with("--diagnostics=") => {
+                diagnostic_format = match arg.trim_start_matches("--diagnostics=") {
+                    "human" => DiagnosticFormat::Human,
+                    "json" => DiagnosticFormat::Json,
+                    other => anyhow::bail!("--diagnostics must be human or json: {other}"),
+                };
             }
             other => {
                 eprintln!("Unknown argument: {other}");
@@ -57,23 +118,80 @@ fn main() -> Result<()> {
         }
     }
 
+    let site = load_site(site_url)?;
+    diagnostics::set_format(diagnostic_format);
+    let reload_url = reload_url
+        .or_else(|| std::env::var("RODIN_RELOAD_URL").ok())
+        .unwrap_or_else(|| DEFAULT_RELOAD_URL.to_string());
+    let reload_token = reload_token.or_else(|| std::env::var("RODIN_RELOAD_TOKEN").ok());
+
+    if watch_mode {
+        return watch::run(&watch::Options {
+            skip_markdown,
+            site,
+            reload_url,
+            reload_token,
+        });
+    }
+
     println!("rodin-content: generating HTML from Typst sources in ./content");
 
-    let mut metas = posts::build_posts(PREAMBLE_PATH, GENERATED_DIR)?;
+    if let Err(e) = build_content(skip_markdown, &site) {
+        diagnostics::emit_error(&e);
+        std::process::exit(1);
+    }
+    let warnings = diagnostics::warning_count();
+    if deny_warnings && warnings > 0 {
+        eprintln!("rodin-content: {warnings} warning(s) with --deny-warnings");
+        std::process::exit(1);
+    }
 
+    println!("done. outputs are under {GENERATED_DIR}");
+
+    if do_reload {
+        trigger_reload(&reload_url, reload_token.as_deref())?;
+    }
+
+    Ok(())
+}
+
+/// `rodin.toml` の [site]。`--site=` があれば URL だけ差し替える
+fn load_site(site_url: Option<String>) -> Result<Site> {
+    let mut site = SiteConfig::load()?.site;
+    if let Some(url) = site_url {
+        site.url = url.trim_end_matches('/').to_string();
+    }
+    Ok(site)
+}
+
+/// ビルドした記事一覧と PGP ページのメタ（watch は次の差分ビルドでこれと比べる）
+struct Generated {
+    metas: Vec<FrontMatter>,
+    pgp: Option<FrontMatter>,
+}
+
+fn build_content(skip_markdown: bool, site: &Site) -> Result<Generated> {
+    let assets = resolver::AssetResolver::new(resolver::ASSET_ROOT);
+    let mut metas = posts::build_posts(PREAMBLE_PATH, GENERATED_DIR, &assets, site)?;
+    export_markdown(&mut metas, skip_markdown, &site.url);
+    posts::build_profile(PREAMBLE_PATH, GENERATED_DIR, &assets, site)?;
+    let pgp = posts::build_pgp(PREAMBLE_PATH, GENERATED_DIR, &assets, site)?;
+    write_listings(&metas, pgp.as_ref(), &assets, site)?;
+    Ok(Generated { metas, pgp })
+}
+
+/// 失敗しても Markdown リンクを外すだけでビルドは止めない
+fn export_markdown(metas: &mut [FrontMatter], skip_markdown: bool, site_url: &str) {
     if skip_markdown {
         for m in metas.iter_mut() {
             m.markdown = None;
         }
         println!("markdown generation skipped (--skip-markdown)");
     } else {
-        match markdown::build_markdown(&mut metas, GENERATED_MD_DIR, PANDOC_FILTER) {
-            Ok(true) => println!("markdown generated for {} posts", metas.len()),
-            Ok(false) => {
-                println!("markdown generation skipped (pandoc missing or failed); disabling markdown links");
-                for m in metas.iter_mut() {
-                    m.markdown = None;
-                }
+        match markdown::build_markdown(metas, GENERATED_MD_DIR, site_url) {
+            Ok(()) => {
+                let exported = metas.iter().filter(|m| m.markdown.is_some()).count();
+                println!("markdown generated for {exported} posts");
             }
             Err(e) => {
                 println!("markdown generation error: {e}; disabling markdown links");
@@ -83,24 +201,54 @@ fn main() -> Result<()> {
             }
         }
     }
+}
 
-    markdown::write_index(&metas, GENERATED_DIR)?;
-    posts::build_home(PREAMBLE_PATH, GENERATED_DIR)?;
-    posts::build_profile(PREAMBLE_PATH, GENERATED_DIR)?;
-    let pgp_meta = posts::build_pgp(PREAMBLE_PATH, GENERATED_DIR)?;
-    let pgp_ref = pgp_meta.as_ref();
-    sitemap::write_sitemap(&metas, pgp_ref, &site_url, DEFAULT_SITEMAP_PATH)?;
-
-    println!("done. outputs are under {GENERATED_DIR}");
+/// 記事一覧を使うもの（index.json・トップページ・サイトマップ・フィード）を書き直す
+fn write_listings(
+    metas: &[FrontMatter],
+    pgp: Option<&FrontMatter>,
+    assets: &resolver::AssetResolver,
+    site: &Site,
+) -> Result<()> {
+    markdown::write_index(metas, GENERATED_DIR)?;
+    // index.json を読むのでこの順
+    posts::build_home(PREAMBLE_PATH, GENERATED_DIR, assets, site)?;
+    let listed = posts::listed_posts(metas);
+    let urls = sitemap::write_sitemap(&listed, pgp, &site.url, DEFAULT_SITEMAP_PATH)?;
+    println!("sitemap generated with {urls} urls");
+    let (entries, tags) = feed::write_feeds(&listed, site, GENERATED_DIR)?;
+    println!("feeds generated with {entries} entries ({tags} tags)");
+    Ok(())
+}
 
-    if do_reload {
-        let url = reload_url
-            .or_else(|| std::env::var("RODIN_RELOAD_URL").ok())
-            .unwrap_or_else(|| DEFAULT_RELOAD_URL.to_string());
-        let token = reload_token.or_else(|| std::env::var("RODIN_RELOAD_TOKEN").ok());
-        trigger_reload(&url, token.as_deref())?;
+fn print_preview_url(args: &[String]) -> Result<()> {
+    let mut slug: Option<&str> = None;
+    let mut ttl_hours = DEFAULT_PREVIEW_TTL_HOURS;
+    let mut site_url: Option<String> = None;
+    for arg in args {
+        if let Some(v) = arg.strip_prefix("--ttl=") {
+            ttl_hours = v
+                .parse()
+                .map_err(|_| anyhow::anyhow!("--ttl must be a number of hours: {v}"))?;
+        } else if let Some(v) = arg.strip_prefix("--site=") {
+            site_url = Some(v.to_string());
+        } else if slug.is_none() && !arg.starts_with('-') {
+            slug = Some(arg);
+        } else {
+            anyhow::bail!("unknown argument for preview-token: {arg}");
+        }
     }
+    let slug = slug.ok_or_else(|| anyhow::anyhow!("usage: rodin-content preview-token <slug>"))?;
+    let secret = std::env::var("RODIN_PREVIEW_SECRET")
+        .or_else(|_| std::env::var("PREVIEW_SECRET"))
+        .map_err(|_| {
+            anyhow::anyhow!("set RODIN_PREVIEW_SECRET (same value as the server's PREVIEW_SECRET)")
+        })?;
 
+    let site = load_site(site_url)?;
+    let expires = chrono::Utc::now().timestamp() + ttl_hours * 3600;
+    let token = preview::preview_token(&secret, slug, expires);
+    println!("{}/preview/{slug}?token={token}", site.url);
     Ok(())
 }
 
@@ -143,14 +291,27 @@ fn trigger_reload(url: &str, token: Option<&str>) -> Result<()> {
 }
 
 fn print_help() {
-    println!("Usage: rodin-content [--skip-markdown] [--site=BASE_URL]");
+    println!("Usage: rodin-content [--skip-markdown] [--site=BASE_URL] [--deny-warnings] [--diagnostics=human|json]");
+    println!("       rodin-content watch [--skip-markdown] [--site=BASE_URL] [--reload-url=U] [--reload-token=T]");
+    println!("       rodin-content check [--diagnostics=json|human] [--deny-warnings]");
+    println!("       rodin-content new <slug> [--title TITLE] [--tags a,b]");
+    println!("       rodin-content preview-token <slug> [--ttl=HOURS] [--site=BASE_URL]");
     println!(
-        "  builds Typst articles in ./content into static/generated (HTML, index.json, sitemap)"
+        "  builds Typst articles in ./content into static/generated (HTML, index.json, sitemap, feeds)"
     );
     println!("  skips font steps; only content generation runs");
-    println!("  --skip-markdown : do not run pandoc even if available");
-    println!("  --site=URL      : override sitemap base (default {DEFAULT_SITE_URL})");
+    println!("  posts: content/**/<name>.typ or content/**/<name>/index.typ (bundle with its own assets)");
+    println!("  RODIN_SLUG_STYLE=name|path : slug from the file/dir name (default) or the joined path (2025-foo)");
+    println!("  --skip-markdown : do not export Markdown");
+    println!("  site settings come from rodin.toml (or the file named by RODIN_CONFIG)");
+    println!("  --site=URL      : override site.url for sitemap/feeds/markdown links (also RODIN_SITE_URL)");
+    println!("  --deny-warnings : exit with 1 if Typst reported any warning");
+    println!("  --diagnostics=F : print Typst errors/warnings to stderr as `human` (default) or `json` (one object per line)");
     println!("  --reload        : call POST {DEFAULT_RELOAD_URL} after build");
     println!("  --reload-url=U  : override reload URL (http:// only)");
     println!("  --reload-token=T: set X-Rodin-Reload-Token header");
+    println!("  watch           : rebuild on changes to content/, static/preamble.typ and static/images, then reload the server (errors do not stop it)");
+    println!("  check           : lint front matter, image references and /blog links without writing anything; exits 1 on errors (drafts only warn)");
+    println!("  new             : create content/<slug>.typ as a draft dated today (site.timezone); never overwrites");
+    println!("  preview-token   : print a signed /preview URL (needs RODIN_PREVIEW_SECRET; default TTL {DEFAULT_PREVIEW_TTL_HOURS}h)");
 }
@@ -0,0 +1,327 @@
+use anyhow::Result;
+use regex::Regex;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+    sync::LazyLock,
+};
+
+use crate::{
+    config::SiteConfig,
+    content::{self, PostSource, SlugStyle},
+    diagnostics::{self, Diagnostic, DiagnosticFormat, Level},
+    frontmatter::{is_valid_slug, parse_datetime, PostStatus},
+    posts::FRONT_MATTER_KEYS,
+    resolver::{self, AssetResolver},
+};
+
+/// `image("foo.png")` とプリアンブルの `img("foo.png")`
+static IMAGE_REF_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\b(?:image|img)\(\s*"([^"\n]+)""#).expect("valid regex"));
+/// 文字列リテラル中の `/blog/<slug>`（サイトの URL 付きも）
+static BLOG_LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#""((?:https?://[^"/\s]+)?)/blog/([^"/?#\s]+)"#).expect("valid regex")
+});
+
+/// `//:` の 1 行
+struct Field<'a> {
+    key: String,
+    value: &'a str,
+    /// ソース中の位置（バイト）
+    start: usize,
+    end: usize,
+}
+
+/// 記事 1 本分の読み込み結果
+struct Post<'a> {
+    source: &'a PostSource,
+    file: String,
+    text: String,
+    draft: bool,
+}
+
+/// `rodin-content check`: 出力を書かずに全記事のフロントマター・画像・内部リンクを検査する
+///
+/// 見つけた問題は診断として出し（既定は 1 行 1 件の JSON）、エラーがあれば 1 で終わる。
+/// 下書きの問題は警告に下げる。
+pub fn run(args: &[String]) -> Result<()> {
+    let mut format = DiagnosticFormat::Json;
+    let mut deny_warnings = false;
+    for arg in args {
+        match arg.as_str() {
+            "--deny-warnings" => deny_warnings = true,
+            "--diagnostics=human" => format = DiagnosticFormat::Human,
+            "--diagnostics=json" => format = DiagnosticFormat::Json,
+            other => anyhow::bail!("unknown argument for check: {other}"),
+        }
+    }
+    diagnostics::set_format(format);
+
+    let config = SiteConfig::load()?;
+    let sources = content::scan_sources(Path::new("content"), SlugStyle::from_env())?;
+    let mut found = Vec::new();
+    let posts: Vec<Post> = sources
+        .iter()
+        .filter_map(|source| {
+            let file = source.display_path();
+            match fs::read_to_string(&source.path) {
+                Ok(text) => Some(Post {
+                    source,
+                    draft: is_draft(&text),
+                    file,
+                    text,
+                }),
+                Err(e) => {
+                    let message = format!("cannot read: {e}");
+                    found.push(finding(Level::Error, "unreadable", &file, message));
+                    None
+                }
+            }
+        })
+        .collect();
+
+    // 下書きは公開されないので、公開記事からのリンク先としては存在しない扱い
+    let mut slugs: HashMap<&str, &Post> = HashMap::new();
+    for post in &posts {
+        let slug = post.source.slug.as_str();
+        if !is_valid_slug(slug) {
+            let message = format!(
+                "slug `{slug}` cannot be served as /blog/{slug} (use letters, digits, `-` and `_`)"
+            );
+            found.push(finding(Level::Error, "invalid-slug", &post.file, message));
+        }
+        if let Some(first) = slugs.get(slug) {
+            let message = format!("slug `{slug}` is also used by {}", first.file);
+            found.push(finding(Level::Error, "duplicate-slug", &post.file, message));
+        } else {
+            slugs.insert(slug, post);
+        }
+    }
+
+    let assets = AssetResolver::new(resolver::ASSET_ROOT);
+    for post in &posts {
+        let level = if post.draft {
+            Level::Warning
+        } else {
+            Level::Error
+        };
+        let mut report = |code: &str, message: String, span: Option<(usize, usize)>| {
+            let diag = finding(level, code, &post.file, message);
+            found.push(match span {
+                Some((start, end)) => at(diag, &post.text, start, end),
+                None => diag,
+            });
+        };
+
+        let fields = front_matter(&post.text);
+        let value = |key: &str| {
+            fields
+                .iter()
+                .rev()
+                .find(|f| f.key == key)
+                .filter(|f| !f.value.is_empty())
+        };
+        for field in &fields {
+            let known =
+                field.key.starts_with("meta>") || FRONT_MATTER_KEYS.contains(&field.key.as_str());
+            if !known {
+                let message = format!("unknown front matter key `{}`", field.key);
+                report("unknown-key", message, Some((field.start, field.end)));
+            }
+        }
+        if value("title").is_none() {
+            report("missing-title", "missing `//: title:`".to_string(), None);
+        }
+        if value("meta>description")
+            .or_else(|| value("meta>og:description"))
+            .is_none()
+        {
+            let message = "missing `//: meta>description:`".to_string();
+            report("missing-description", message, None);
+        }
+        if let Some(field) = value("status") {
+            if field.value.parse::<PostStatus>().is_err() {
+                let message = format!(
+                    "unknown status `{}` (draft, unlisted or published)",
+                    field.value
+                );
+                report("invalid-status", message, Some((field.start, field.end)));
+            }
+        }
+
+        let mut dates = BTreeMap::new();
+        for key in ["date", "updated", "publish_at"] {
+            let Some(field) = value(key) else {
+                continue;
+            };
+            match parse_datetime(field.value) {
+                Some(parsed) => {
+                    dates.insert(key, parsed);
+                }
+                None => {
+                    let message = format!("`{key}` is not a date: `{}`", field.value);
+                    report("invalid-date", message, Some((field.start, field.end)));
+                }
+            }
+        }
+        if let (Some(published), Some(updated), Some(field)) =
+            (dates.get("date"), dates.get("updated"), value("updated"))
+        {
+            if updated < published {
+                let message = format!(
+                    "`updated` ({}) is earlier than `date` ({})",
+                    field.value,
+                    value("date").map_or("", |f| f.value)
+                );
+                report(
+                    "updated-before-published",
+                    message,
+                    Some((field.start, field.end)),
+                );
+            }
+        }
+
+        if let Some(field) = value("breadcrumbs") {
+            for key in field
+                .value
+                .split(',')
+                .map(str::trim)
+                .filter(|k| !k.is_empty())
+            {
+                if !config.breadcrumbs.contains_key(key) {
+                    let message = format!("unknown breadcrumb `{key}` (not in [breadcrumbs])");
+                    report(
+                        "unknown-breadcrumb",
+                        message,
+                        Some((field.start, field.end)),
+                    );
+                }
+            }
+        }
+
+        for caps in IMAGE_REF_RE.captures_iter(&post.text) {
+            let Some(m) = caps.get(1) else {
+                continue;
+            };
+            if !image_exists(m.as_str(), post.source.bundle_dir.as_deref(), &assets) {
+                let message = format!("image `{}` not found", m.as_str());
+                report("missing-image", message, Some((m.start(), m.end())));
+            }
+        }
+
+        for caps in BLOG_LINK_RE.captures_iter(&post.text) {
+            let (Some(origin), Some(m)) = (caps.get(1), caps.get(2)) else {
+                continue;
+            };
+            // 外部サイトの /blog/ は対象外
+            if !origin.as_str().is_empty() && origin.as_str() != config.site.url {
+                continue;
+            }
+            let slug = [".pdf", ".md", ".typ"]
+                .iter()
+                .find_map(|ext| m.as_str().strip_suffix(ext))
+                .unwrap_or(m.as_str());
+            let message = match slugs.get(slug) {
+                Some(target) if target.draft && !post.draft => {
+                    format!("link to `/blog/{slug}`, which is a draft")
+                }
+                Some(_) => continue,
+                None => format!("link to `/blog/{slug}`, which does not exist"),
+            };
+            report("broken-link", message, Some((m.start(), m.end())));
+        }
+    }
+
+    found.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    found.iter().for_each(diagnostics::emit);
+    let errors = found.iter().filter(|d| d.level == Level::Error).count();
+    let warnings = found.len() - errors;
+    println!(
+        "rodin-content check: {} posts, {errors} error(s), {warnings} warning(s)",
+        posts.len()
+    );
+    if errors > 0 || (deny_warnings && warnings > 0) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn front_matter(text: &str) -> Vec<Field<'_>> {
+    let mut fields = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let Some(rest) = line.trim_end().strip_prefix("//:") else {
+            continue;
+        };
+        let trimmed = rest.trim();
+        let (key, value) = match trimmed.strip_prefix("meta>") {
+            Some(meta) => {
+                let (k, v) = meta.split_once(':').unwrap_or((meta, ""));
+                (format!("meta>{}", k.trim()), v)
+            }
+            None => {
+                let (k, v) = trimmed.split_once(':').unwrap_or((trimmed, ""));
+                (k.trim().to_string(), v)
+            }
+        };
+        fields.push(Field {
+            key,
+            value: value.trim(),
+            start,
+            end: start + line.trim_end().len(),
+        });
+    }
+    fields
+}
+
+/// `//: status: draft`（最後に書いたものが効く）
+fn is_draft(text: &str) -> bool {
+    front_matter(text)
+        .iter()
+        .rev()
+        .find(|f| f.key == "status")
+        .is_some_and(|f| f.value.parse() == Ok(PostStatus::Draft))
+}
+
+/// バンドル内・static/ 以下・`/assets/...` の URL のどれかで見つかればよい
+fn image_exists(path: &str, bundle_dir: Option<&Path>, assets: &AssetResolver) -> bool {
+    if path.starts_with("http://") || path.starts_with("https://") || path.starts_with("data:") {
+        return true;
+    }
+    let rel = path.trim_start_matches("./").trim_start_matches('/');
+    bundle_dir.is_some_and(|dir| dir.join(rel).is_file())
+        || rel
+            .strip_prefix("assets/")
+            .is_some_and(|p| Path::new(resolver::ASSET_ROOT).join(p).is_file())
+        || assets.read(path).is_some()
+}
+
+fn finding(level: Level, code: &str, file: &str, message: String) -> Diagnostic {
+    Diagnostic {
+        code: Some(code.to_string()),
+        ..Diagnostic::plain(level, file, message)
+    }
+}
+
+/// `start..end`（バイト位置）を行・列にして抜粋を付ける
+fn at(mut diag: Diagnostic, text: &str, start: usize, end: usize) -> Diagnostic {
+    let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+    let column = text[line_start..start].chars().count() + 1;
+    let width = text[start..end.clamp(start, line_end)]
+        .chars()
+        .count()
+        .max(1);
+    diag.line = Some(text[..start].matches('\n').count() + 1);
+    diag.column = Some(column);
+    diag.end_column = Some(column + width);
+    diag.excerpt = Some(
+        text[line_start..line_end]
+            .trim_end_matches('\r')
+            .to_string(),
+    );
+    diag
+}
@@ -0,0 +1,90 @@
+use anyhow::{bail, Context, Result};
+use chrono::{FixedOffset, Utc};
+use std::{fs, path::Path};
+
+use crate::{
+    config::SiteConfig,
+    content::{self, SlugStyle},
+    frontmatter::is_valid_slug,
+};
+
+const CONTENT_DIR: &str = "content";
+/// content/<slug>.typ から見たプリアンブル
+const PREAMBLE_IMPORT: &str = r#"#import "../static/preamble.typ": *"#;
+
+/// `rodin-content new <slug> [--title T] [--tags a,b]`: 下書きの記事を content/<slug>.typ に作る
+///
+/// 日付は rodin.toml の `site.timezone` での今日。既にあるスラッグは上書きしない。
+pub fn run(args: &[String]) -> Result<()> {
+    let mut slug: Option<&str> = None;
+    let mut title: Option<String> = None;
+    let mut tags: Vec<String> = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| -> Result<String> {
+            match arg
+                .strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix('='))
+            {
+                Some(v) => Ok(v.to_string()),
+                None => args
+                    .next()
+                    .cloned()
+                    .with_context(|| format!("{name} needs a value")),
+            }
+        };
+        if arg == "--title" || arg.starts_with("--title=") {
+            title = Some(value("--title")?);
+        } else if arg == "--tags" || arg.starts_with("--tags=") {
+            tags.extend(
+                value("--tags")?
+                    .split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty()),
+            );
+        } else if slug.is_none() && !arg.starts_with('-') {
+            slug = Some(arg);
+        } else {
+            bail!("unknown argument for new: {arg}");
+        }
+    }
+    let slug = slug.context("usage: rodin-content new <slug> [--title T] [--tags a,b]")?;
+    if !is_valid_slug(slug) {
+        bail!("slug `{slug}` cannot be served as /blog/{slug}; use letters, digits, `-` and `_`");
+    }
+
+    let existing = content::scan_sources(Path::new(CONTENT_DIR), SlugStyle::from_env())?;
+    if let Some(post) = existing.iter().find(|p| p.slug == slug) {
+        bail!("slug `{slug}` is already used by {}", post.display_path());
+    }
+    let path = Path::new(CONTENT_DIR).join(format!("{slug}.typ"));
+    let bundle = Path::new(CONTENT_DIR).join(slug);
+    if path.exists() || bundle.exists() {
+        bail!("{} already exists", path.display());
+    }
+
+    let site = SiteConfig::load()?.site;
+    let offset: FixedOffset = site.timezone.parse()?;
+    let date = Utc::now().with_timezone(&offset).format("%Y-%m-%d");
+    // 1 行に収める（//: の値は行末まで）
+    let title = title
+        .as_deref()
+        .unwrap_or(slug)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut source = format!("{PREAMBLE_IMPORT}\n\n//: title: {title}\n//: date: {date}\n");
+    if !tags.is_empty() {
+        source.push_str(&format!("//: tags: {}\n", tags.join(", ")));
+    }
+    // 説明は空のままだと `rodin-content check` が指摘する
+    source.push_str("//: meta>description:\n//: status: draft\n\n");
+
+    fs::write(&path, source)?;
+    println!(
+        "created {} (draft; remove `//: status: draft` to publish)",
+        path.display()
+    );
+    Ok(())
+}
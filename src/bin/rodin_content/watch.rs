@@ -0,0 +1,235 @@
+use anyhow::Result;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    build_content,
+    config::Site,
+    content::{self, PostSource, SlugStyle},
+    diagnostics, export_markdown,
+    frontmatter::FrontMatter,
+    posts,
+    resolver::{self, AssetResolver},
+    trigger_reload, write_listings, Generated, GENERATED_DIR, PREAMBLE_PATH,
+};
+
+const CONTENT_DIR: &str = "content";
+const IMAGES_DIR: &str = "static/images";
+/// mtime を見比べる間隔（ファイル通知の仕組みには頼らない）
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+/// エディタの保存（一時ファイルに書いて rename など）が終わるのを待つ
+const SETTLE_DELAY: Duration = Duration::from_millis(150);
+
+pub struct Options {
+    pub skip_markdown: bool,
+    pub site: Site,
+    pub reload_url: String,
+    pub reload_token: Option<String>,
+}
+
+/// 変わったファイルから決めた作り直す範囲
+#[derive(Default)]
+struct Plan {
+    /// プリアンブルが変わったら全部
+    everything: bool,
+    /// static/images や記事でない content 内のファイル（Markdown も全部書き直す）
+    all_posts: bool,
+    posts: HashSet<String>,
+    home: bool,
+    profile: bool,
+    pgp: bool,
+}
+
+impl Plan {
+    fn from_changes(changed: &[PathBuf], sources: &[PostSource]) -> Self {
+        let mut plan = Plan::default();
+        let content_dir = Path::new(CONTENT_DIR);
+        for path in changed {
+            if path == Path::new(PREAMBLE_PATH) {
+                plan.everything = true;
+            } else if path.starts_with(IMAGES_DIR) {
+                plan.all_posts = true;
+            } else if path == &content_dir.join("_home.typ") {
+                plan.home = true;
+            } else if path == &content_dir.join("_profile.typ") {
+                plan.profile = true;
+            } else if path == &content_dir.join("_pgp.typ") {
+                plan.pgp = true;
+            } else if let Some(post) = sources.iter().find(|s| {
+                &s.path == path || s.bundle_dir.as_ref().is_some_and(|d| path.starts_with(d))
+            }) {
+                plan.posts.insert(post.slug.clone());
+            } else {
+                // 消した記事や、記事から読み込まれる共通ファイル
+                plan.all_posts = true;
+            }
+        }
+        plan
+    }
+
+    fn touches(&self, slug: &str) -> bool {
+        self.all_posts || self.posts.contains(slug)
+    }
+}
+
+/// `rodin-content watch`: 最初に全部ビルドし、あとは保存のたびに差分だけ作り直してサーバーに再読み込みさせる
+///
+/// コンパイルエラーは表示するだけで終了せず、次の保存を待つ。
+pub fn run(opts: &Options) -> Result<()> {
+    println!(
+        "rodin-content: watching {CONTENT_DIR}/, {PREAMBLE_PATH} and {IMAGES_DIR}/ (Ctrl-C to stop)"
+    );
+    let mut snapshot = scan();
+    let mut last = match build_content(opts.skip_markdown, &opts.site) {
+        Ok(generated) => {
+            reload(opts);
+            Some(generated)
+        }
+        Err(e) => {
+            diagnostics::emit_error(&e);
+            None
+        }
+    };
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let mut current = scan();
+        if current == snapshot {
+            continue;
+        }
+        loop {
+            thread::sleep(SETTLE_DELAY);
+            let again = scan();
+            if again == current {
+                break;
+            }
+            current = again;
+        }
+        let changed = changed_paths(&snapshot, &current);
+        snapshot = current;
+        for path in &changed {
+            println!("changed: {}", path.display());
+        }
+
+        match rebuild(opts, &mut last, &changed) {
+            Ok(()) => reload(opts),
+            Err(e) => {
+                diagnostics::emit_error(&e);
+                println!("build failed; waiting for the next change");
+            }
+        }
+    }
+}
+
+fn rebuild(opts: &Options, last: &mut Option<Generated>, changed: &[PathBuf]) -> Result<()> {
+    let site = &opts.site;
+    let sources = content::collect_sources(Path::new(CONTENT_DIR), SlugStyle::from_env())?;
+    let plan = Plan::from_changes(changed, &sources);
+    // 前回が失敗していたら比べる相手がないので全部
+    let Some(prev) = last.as_mut().filter(|_| !plan.everything) else {
+        *last = Some(build_content(opts.skip_markdown, site)?);
+        return Ok(());
+    };
+
+    let assets = AssetResolver::new(resolver::ASSET_ROOT);
+    let mut listings_changed = false;
+    if plan.profile {
+        posts::build_profile(PREAMBLE_PATH, GENERATED_DIR, &assets, site)?;
+    }
+    if plan.pgp {
+        let pgp = posts::build_pgp(PREAMBLE_PATH, GENERATED_DIR, &assets, site)?;
+        listings_changed |= !same_metas(pgp.as_slice(), prev.pgp.as_slice());
+        prev.pgp = pgp;
+    }
+    if plan.all_posts || !plan.posts.is_empty() {
+        // 触っていない記事はビルドキャッシュから戻るので、コンパイルされるのは変わった記事だけ
+        let mut metas = posts::build_posts(PREAMBLE_PATH, GENERATED_DIR, &assets, site)?;
+        let mut touched: Vec<FrontMatter> = metas
+            .iter()
+            .filter(|m| plan.touches(&m.slug))
+            .cloned()
+            .collect();
+        export_markdown(&mut touched, opts.skip_markdown, &site.url);
+        for meta in &mut metas {
+            let exported = touched
+                .iter()
+                .chain(&prev.metas)
+                .find(|m| m.slug == meta.slug);
+            meta.markdown = exported.and_then(|m| m.markdown.clone());
+        }
+        listings_changed |= !same_metas(&metas, &prev.metas);
+        prev.metas = metas;
+    }
+
+    if listings_changed {
+        write_listings(&prev.metas, prev.pgp.as_ref(), &assets, site)?;
+    } else if plan.home {
+        posts::build_home(PREAMBLE_PATH, GENERATED_DIR, &assets, site)?;
+    }
+    Ok(())
+}
+
+/// サーバーが起動していなくても watch は続ける
+fn reload(opts: &Options) {
+    if let Err(e) = trigger_reload(&opts.reload_url, opts.reload_token.as_deref()) {
+        println!("reload skipped: {e}");
+    }
+}
+
+fn same_metas(a: &[FrontMatter], b: &[FrontMatter]) -> bool {
+    match (serde_json::to_string(a), serde_json::to_string(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn scan() -> BTreeMap<PathBuf, SystemTime> {
+    let mut files = BTreeMap::new();
+    let mut stack: Vec<PathBuf> = [CONTENT_DIR, PREAMBLE_PATH, IMAGES_DIR]
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+    while let Some(path) = stack.pop() {
+        let Ok(meta) = fs::metadata(&path) else {
+            continue;
+        };
+        if meta.is_dir() {
+            let Ok(entries) = fs::read_dir(&path) else {
+                continue;
+            };
+            stack.extend(
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter(|e| !is_scratch_file(&e.file_name().to_string_lossy()))
+                    .map(|e| e.path()),
+            );
+        } else if let Ok(modified) = meta.modified() {
+            files.insert(path, modified);
+        }
+    }
+    files
+}
+
+/// content/.git やエディタの一時ファイル（.foo.swp, foo~, #foo#）
+fn is_scratch_file(name: &str) -> bool {
+    name.starts_with('.') || name.ends_with('~') || name.starts_with('#')
+}
+
+fn changed_paths(
+    before: &BTreeMap<PathBuf, SystemTime>,
+    after: &BTreeMap<PathBuf, SystemTime>,
+) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = after
+        .iter()
+        .filter(|(path, modified)| before.get(*path) != Some(*modified))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed.extend(before.keys().filter(|p| !after.contains_key(*p)).cloned());
+    changed.sort();
+    changed
+}
@@ -0,0 +1,76 @@
+use crate::frontmatter::FrontMatter;
+use itertools::Itertools;
+
+/// Written into `home.html` where `POSTS_LIST_PLACEHOLDER` was; the server fills it
+/// with [`post_cards_html`] for the posts visible at that moment.
+pub const POSTS_LIST_TOKEN: &str = "__POSTS_LIST_PLACEHOLDER__";
+
+/// Cards for the five most recent posts (pass only the posts that should be listed).
+pub fn post_cards_html(metas: &[FrontMatter]) -> String {
+    metas
+        .iter()
+        // sort by published_at desc
+        .sorted_by(|a, b| {
+            let a_time = a
+                .published_at
+                .as_deref()
+                .or(a.updated_at.as_deref())
+                .unwrap_or("");
+            let b_time = b
+                .published_at
+                .as_deref()
+                .or(b.updated_at.as_deref())
+                .unwrap_or("");
+            b_time.cmp(a_time)
+        })
+        .take(5)
+        .map(|post| {
+            let title = post.title.as_deref().unwrap_or(&post.slug);
+            let slug = &post.slug;
+            let date = post.published_at.as_deref().unwrap_or("");
+            let updated = post.updated_at.as_deref().unwrap_or(date);
+            let tags = &post.tags;
+            let description = post
+                .meta
+                .get("description")
+                .or_else(|| post.meta.get("og:description"))
+                .map(String::as_str)
+                .unwrap_or("");
+
+            let tags_html = if !tags.is_empty() {
+                let chips: String = tags
+                    .iter()
+                    .map(|t| format!("<span>#{}</span>", t))
+                    .collect::<Vec<_>>()
+                    .join("");
+                format!("<div class=\"flex flex-wrap gap-2 pt-1\">{chips}</div>")
+            } else {
+                String::new()
+            };
+
+            let updated_html = if !updated.is_empty() {
+                format!("Updated: {}", updated)
+            } else {
+                String::new()
+            };
+            let published_html = if !date.is_empty() {
+                format!("Published: {}", date)
+            } else {
+                String::new()
+            };
+
+            format!(
+                "<article class=\"post-card\">\
+                    <div>\
+                        <a href=\"/blog/{slug}\" class=\"not-prose\">{title}</a>\
+                        <div>{updated_html}</div>\
+                    </div>\
+                    <div>{description}</div>\
+                    <div>{published_html}</div>\
+                    {tags_html}\
+                </article>"
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("")
+}
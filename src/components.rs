@@ -1,73 +1,88 @@
 mod search;
+mod tags;
 pub use search::SearchPage;
+pub use tags::TagsPage;
 
 use leptos::prelude::*;
 
-use crate::app::render::BlogListItem;
-use crate::frontmatter::FrontMatter;
+use crate::app::{
+    config,
+    render::{BlogListItem, PostNav, SeriesNav},
+};
+use crate::frontmatter::{FrontMatter, TocEntry};
+use crate::images::ResponsiveImage;
+
+/// トップのヒーロー画像の元ファイル（static/images/urumashi.jpg）
+pub const HERO_IMAGE_URL: &str = "/assets/images/urumashi.jpg";
+pub const HERO_IMAGE_SIZES: &str = "100vw";
 
 #[component]
 pub fn BlogListPage(
     client_ip: String,
+    title: String,
     posts: Vec<BlogListItem>,
     current_page: u32,
     total_pages: u32,
+    base_url: String,
 ) -> impl IntoView {
     view! {
         <div class="blog-wrapper">
             <HeaderBar
-                title="すずねーう".to_string()
+                title=config().site.name.clone()
                 subtitle=format!("{client_ip}")
                 current_path="/blog".to_string()
             />
             <main class="blog-container">
-                <h1 class="blog-list-title">"ブログ記事一覧"</h1>
+                <h1 class="blog-list-title">{title}</h1>
                 <div class="posts-list">
-                    {posts.into_iter().map(|post| {
-                        let url = format!("/blog/{}", post.slug);
-                        let published = post.published_at.clone().unwrap_or_default();
-                        let updated = post.updated_at.clone().unwrap_or_default();
-                        let description = post.description.clone().unwrap_or_default();
-                        let tags = post.tags.clone();
-                        let published_html = if !published.is_empty() {
-                            format!("Published: {}", published)
-                        } else {
-                            String::new()
-                        };
-                        let updated_html = if !updated.is_empty() && updated != published {
-                            format!("Updated: {}", updated)
-                        } else {
-                            String::new()
-                        };
-                        let tags_html = if !tags.is_empty() {
-                            let chips: String = tags
-                                .iter()
-                                .map(|t| format!("<span>#{}</span>", t))
-                                .collect::<Vec<_>>()
-                                .join("");
-                            format!("<div class=\"flex flex-wrap gap-2 pt-1\">{}</div>", chips)
-                        } else {
-                            String::new()
-                        };
-                        view! {
-                            <article class="post-card">
-                                <div>
-                                    <a href=url class="not-prose">{post.title}</a>
-                                    <div>{updated_html}</div>
-                                </div>
-                                <div>{description}</div>
-                                <div>{published_html}</div>
-                                <div inner_html=tags_html></div>
-                            </article>
-                        }
-                    }).collect_view()}
+                    {posts.into_iter().map(|post| view! { <PostCard post=post /> }).collect_view()}
                 </div>
-                <Pagination current_page=current_page total_pages=total_pages base_url="/blog".to_string() />
+                <Pagination current_page=current_page total_pages=total_pages base_url=base_url />
             </main>
         </div>
     }
 }
 
+#[component]
+fn PostCard(post: BlogListItem) -> impl IntoView {
+    let url = format!("/blog/{}", post.slug);
+    let published = post.published_at.clone().unwrap_or_default();
+    let updated = post.updated_at.clone().unwrap_or_default();
+    let description = post.description.clone().unwrap_or_default();
+    let tags = post.tags.clone();
+    let published_html = if !published.is_empty() {
+        format!("Published: {}", published)
+    } else {
+        String::new()
+    };
+    let updated_html = if !updated.is_empty() && updated != published {
+        format!("Updated: {}", updated)
+    } else {
+        String::new()
+    };
+    let tags_html = if !tags.is_empty() {
+        let chips: String = tags
+            .iter()
+            .map(|t| format!("<span>#{}</span>", t))
+            .collect::<Vec<_>>()
+            .join("");
+        format!("<div class=\"flex flex-wrap gap-2 pt-1\">{}</div>", chips)
+    } else {
+        String::new()
+    };
+    view! {
+        <article class="post-card">
+            <div>
+                <a href=url class="not-prose">{post.title}</a>
+                <div>{updated_html}</div>
+            </div>
+            <div>{description}</div>
+            <div>{published_html}</div>
+            <div inner_html=tags_html></div>
+        </article>
+    }
+}
+
 #[component]
 fn Pagination(current_page: u32, total_pages: u32, base_url: String) -> impl IntoView {
     if total_pages <= 1 {
@@ -85,11 +100,13 @@ fn Pagination(current_page: u32, total_pages: u32, base_url: String) -> impl Int
         None
     };
 
+    // 検索ページは base_url に ?q= が付いている
+    let separator = if base_url.contains('?') { '&' } else { '?' };
     let page_url = |p: u32| {
         if p == 1 {
             base_url.clone()
         } else {
-            format!("{}?page={}", base_url, p)
+            format!("{}{}page={}", base_url, separator, p)
         }
     };
 
@@ -116,18 +133,20 @@ pub fn BlogPage(
     html_content: String,
     meta: FrontMatter,
     current_path: String,
+    #[prop(default = None)] series: Option<SeriesNav>,
+    #[prop(default = None)] read_next: Option<PostNav>,
 ) -> impl IntoView {
     let article_title = meta
         .title
         .clone()
-        .unwrap_or_else(|| "すずねーう".to_string());
+        .unwrap_or_else(|| config().site.name.clone());
     let subtitle_view = meta.subtitle.clone().unwrap_or_default();
     let crumbs = meta.breadcrumbs.clone();
-    let registry = crate::app::render::breadcrumb_registry();
+    let registry = &config().breadcrumbs;
     view! {
         <div class="blog-wrapper">
             <HeaderBar
-                title="すずねーう".to_string()
+                title=config().site.name.clone()
                 subtitle=format!("{client_ip}")
                 current_path=current_path.clone()
             />
@@ -140,8 +159,8 @@ pub fn BlogPage(
                                 {crumbs.iter().enumerate().map(|(idx, key)| {
                                     let is_last = idx == last;
                                     let (label, href_opt) = registry
-                                        .get(key.as_str())
-                                        .map(|(n, h)| (n.to_string(), Some(*h)))
+                                        .get(key)
+                                        .map(|c| (c.name.clone(), Some(c.path.clone())))
                                         .unwrap_or_else(|| (key.clone(), None));
                                     view! {
                                         <li>
@@ -149,7 +168,7 @@ pub fn BlogPage(
                                                 view! { <span class="blog-breadcrumb-label">{label.clone()}</span> }.into_any()
                                             } else {
                                                 view! {
-                                                    <a href={href_opt.unwrap_or("#")} class="blog-breadcrumb-label">{label.clone()}</a>
+                                                    <a href={href_opt.clone().unwrap_or_else(|| "#".to_string())} class="blog-breadcrumb-label">{label.clone()}</a>
                                                 }.into_any()
                                             }}
                                             {view! { <span class="separator">/</span> }.into_any()}
@@ -169,63 +188,73 @@ pub fn BlogPage(
                     updated=meta.updated_at.clone()
                     reading_minutes=meta.reading_minutes
                     slug=current_path.strip_prefix("/blog/").map(|s| s.to_string())
+                    has_pdf=meta.pdf.is_some()
                 />
                 <ShowTags tags=meta.tags.clone() />
+                {series.map(|nav| view! { <SeriesBox nav=nav /> })}
+                {(!meta.hide_toc).then(|| view! { <TableOfContents toc=meta.toc.clone() /> })}
                 <article inner_html=html_content></article>
+                {read_next.map(|nav| view! { <ReadNext nav=nav /> })}
             </main>
         </div>
     }
 }
 
+/// トップのヒーロー画像（ビルドで作った縮小版から選ばせる）
 #[component]
-pub fn TopPage(client_ip: String, home_html: String, current_path: String) -> impl IntoView {
+fn HeroImage(image: Option<ResponsiveImage>) -> impl IntoView {
+    let Some(image) = image else {
+        // マニフェストが無い（画像の生成に失敗した）ときは元画像をそのまま出す
+        return view! {
+            <img src=HERO_IMAGE_URL alt="" loading="eager" decoding="async" fetchpriority="high" />
+        }
+        .into_any();
+    };
+    let avif = ResponsiveImage::srcset(&image.avif);
+    let webp = ResponsiveImage::srcset(&image.webp);
+    let fallback = ResponsiveImage::srcset(&image.fallback);
+    let src = image.fallback_src().unwrap_or(HERO_IMAGE_URL).to_string();
+    let style = format!("background:url({}) center/cover no-repeat", image.lqip);
+    view! {
+        <picture>
+            <source type="image/avif" srcset=avif sizes=HERO_IMAGE_SIZES />
+            <source type="image/webp" srcset=webp sizes=HERO_IMAGE_SIZES />
+            <img
+                src=src
+                srcset=fallback
+                sizes=HERO_IMAGE_SIZES
+                width=image.width
+                height=image.height
+                style=style
+                alt=""
+                loading="eager"
+                decoding="async"
+                fetchpriority="high"
+            />
+        </picture>
+    }
+    .into_any()
+}
+
+#[component]
+pub fn TopPage(
+    client_ip: String,
+    home_html: String,
+    current_path: String,
+    #[prop(default = None)] hero: Option<ResponsiveImage>,
+) -> impl IntoView {
+    let site = &config().site;
     view! {
         <div class="top-container">
             <div class="top-hero">
-                <picture
-                    data-deferred-bg
-                    data-bg-sizes="(max-width: 640px) 320px, (max-width: 1024px) 640px, 1200px"
-                >
-                    <source
-                        media="(max-width: 768px)"
-                        type="image/avif"
-                        srcset="/assets/images/urumashi/urumashi-1280.avif 1280w"
-                    />
-                    <source
-                        type="image/avif"
-                        srcset="/assets/images/urumashi/urumashi-1280-low.avif 1280w, /assets/images/urumashi/urumashi-1920-low.avif 1920w, /assets/images/urumashi/urumashi-2560-low.avif 2560w"
-                        data-hi-srcset="/assets/images/urumashi/urumashi-1280.avif 1280w, /assets/images/urumashi/urumashi-1920.avif 1920w, /assets/images/urumashi/urumashi-2560.avif 2560w"
-                    />
-                    <source
-                        media="(max-width: 768px)"
-                        type="image/webp"
-                        data-srcset="/assets/images/urumashi/urumashi-1280.webp 1280w"
-                    />
-                    <source
-                        type="image/webp"
-                        data-srcset="/assets/images/urumashi/urumashi-1280.webp 1280w, /assets/images/urumashi/urumashi-1920.webp 1920w, /assets/images/urumashi/urumashi-2560.webp 2560w"
-                    />
-                    <img
-                        src="/assets/images/urumashi/urumashi-1280-low.avif"
-                        srcset="/assets/images/urumashi/urumashi-1280-low.avif 1280w"
-                        data-src="/assets/images/urumashi/urumashi-1280.jpg"
-                        data-srcset="/assets/images/urumashi/urumashi-1280.jpg 1280w, /assets/images/urumashi/urumashi-1920.jpg 1920w, /assets/images/urumashi/urumashi-2560.jpg 2560w"
-                        data-sizes="(max-width: 640px) 320px, (max-width: 1024px) 640px, 1200px"
-                        width="2560"
-                        height="1920"
-                        alt=""
-                        loading="eager"
-                        decoding="async"
-                        fetchpriority="high"
-                    />
-                </picture>
+                <HeroImage image=hero />
                 <div class="paint"></div>
             </div>
 
             <div class="top-content">
                 <div class="top-header">
                     <HeaderBar
-                        title="すずねーう".to_string()
+                        title=config().site.name.clone()
                         subtitle=format!("{client_ip}")
                         current_path=current_path.clone()
                     />
@@ -234,18 +263,24 @@ pub fn TopPage(client_ip: String, home_html: String, current_path: String) -> im
                     <div class="top-profcard">
                         <div class="top-avatar">
                             <div>
-                                <img src="/assets/images/suzuneu.webp" alt="icon" />
+                                <img src=site.avatar.clone() alt="icon" />
                             </div>
                         </div>
                         <div class="top-name">
-                            <div class="top-name-title">"すずねーう"</div>
-                            <div class="top-name-subtitle">"自称プログラマー"</div>
+                            <div class="top-name-title">{site.author.clone()}</div>
+                            <div class="top-name-subtitle">{site.tagline.clone()}</div>
                         </div>
                         <div class="top-social">
-                            <SocialIcon kind="X" href="https://x.com/suzuneu_discord" class="icon-x" />
-                            <SocialIcon kind="Twitter" href="https://x.com/suzuneu_discord" class="icon-twitter hidden" />
-                            <SocialIcon kind="GitHub" href="https://github.com/waki285" class="" />
-                            <SocialIcon kind="Discord" href="https://discord.com/users/717028469992587315" class="" />
+                            {config().social.iter().map(|link| match link.kind.as_str() {
+                                // Shift を押している間だけ旧アイコンに切り替わる（home.js）
+                                "X" => view! {
+                                    <SocialIcon kind="X" href=link.url.clone() class="icon-x" />
+                                    <SocialIcon kind="Twitter" href=link.url.clone() class="icon-twitter hidden" />
+                                }.into_any(),
+                                kind => view! {
+                                    <SocialIcon kind=kind href=link.url.clone() class="" />
+                                }.into_any(),
+                            }).collect_view()}
                         </div>
                         <div class="top-profile-link">
                             <a
@@ -268,8 +303,8 @@ pub fn TopPage(client_ip: String, home_html: String, current_path: String) -> im
 }
 
 #[component]
-fn SocialIcon(kind: &'static str, href: &'static str, class: &'static str) -> impl IntoView {
-    let (path, view_box, fill) = match kind {
+fn SocialIcon(#[prop(into)] kind: String, href: String, class: &'static str) -> impl IntoView {
+    let (path, view_box, fill) = match kind.as_str() {
         "X" => (
             "M18.244 3.515h3.308l-7.227 7.83 8.502 9.633H16.89l-5.295-6.116-6.06 6.116H2.227l7.73-7.81L1.727 3.515H7.11l4.79 5.545zm-1.16 16.323h1.833L7.07 5.99H5.104z",
             "0 0 24 24",
@@ -322,6 +357,7 @@ fn SocialIcon(kind: &'static str, href: &'static str, class: &'static str) -> im
 #[component]
 fn HeaderBar(title: String, subtitle: String, current_path: String) -> impl IntoView {
     let title_clone = title.clone();
+    let avatar = config().site.avatar.clone();
     let home_active = current_path == "/";
     let blog_active = current_path.starts_with("/blog");
     let profile_active = current_path.starts_with("/profile");
@@ -334,7 +370,7 @@ fn HeaderBar(title: String, subtitle: String, current_path: String) -> impl Into
                 <nav>
                     <div class="header-logo">
                         <a href="/" aria-label="home">
-                            <img src="/assets/images/suzuneu.webp" alt="" width="48" height="48" />
+                            <img src=avatar.clone() alt="" width="48" height="48" />
                             <span>{title.clone()}</span>
                         </a>
                         <ShowIp subtitle=subtitle.clone() />
@@ -391,7 +427,7 @@ fn HeaderBar(title: String, subtitle: String, current_path: String) -> impl Into
             >
                 <nav>
                     <a href="/">
-                        <img src="/assets/images/suzuneu.webp" alt="" width="48" height="48" />
+                        <img src=avatar.clone() alt="" width="48" height="48" />
                         <span>{title_clone}</span>
                     </a>
                     <div class="header-menu">
@@ -465,12 +501,17 @@ fn MetaRow(
     updated: Option<String>,
     reading_minutes: Option<u32>,
     slug: Option<String>,
+    #[prop(default = false)] has_pdf: bool,
 ) -> impl IntoView {
     let pub_text = published.unwrap_or_else(|| "N/A".to_string());
     let upd_text = updated.unwrap_or_else(|| pub_text.clone());
     let pub_dt = pub_text.clone();
     let upd_dt = upd_text.clone();
     let read_label = reading_minutes.map(|m| format!("読むのに約 {} 分", m));
+    let pdf_url = slug
+        .as_ref()
+        .filter(|_| has_pdf)
+        .map(|s| format!("/blog/{s}.pdf"));
     view! {
         <div class="meta-row">
             <span>
@@ -482,6 +523,11 @@ fn MetaRow(
                 <time datetime={upd_dt}>{upd_text}</time>
             </span>
             {read_label.map(|txt| view! { <span>{txt}</span> })}
+            {pdf_url.map(|url| view! {
+                <span>
+                    <a href=url class="pdf-download" download>"PDF 版"</a>
+                </span>
+            })}
             {slug.map(|s| {
                 let typ_url = format!("/blog/{s}.typ");
                 let md_url = format!("/blog/{s}.md");
@@ -498,16 +544,113 @@ fn MetaRow(
     }
 }
 
+/// 見出しが 2 つ以上あるときだけ出す目次
+#[component]
+fn TableOfContents(toc: Vec<TocEntry>) -> impl IntoView {
+    fn count(entries: &[TocEntry]) -> usize {
+        entries.iter().map(|e| 1 + count(&e.children)).sum()
+    }
+    fn toc_list(entries: Vec<TocEntry>) -> AnyView {
+        view! {
+            <ol>
+                {entries.into_iter().map(|entry| {
+                    let href = format!("#{}", entry.id);
+                    let children = (!entry.children.is_empty()).then(|| toc_list(entry.children));
+                    view! {
+                        <li>
+                            <a href=href>{entry.text}</a>
+                            {children}
+                        </li>
+                    }
+                }).collect_view()}
+            </ol>
+        }
+        .into_any()
+    }
+
+    (count(&toc) >= 2).then(|| {
+        view! {
+            <details class="blog-toc not-prose" open>
+                <summary>"目次"</summary>
+                <nav role="doc-toc" aria-label="目次">{toc_list(toc)}</nav>
+            </details>
+        }
+    })
+}
+
+#[component]
+fn SeriesBox(nav: SeriesNav) -> impl IntoView {
+    let index_url = crate::frontmatter::series_path(&nav.id);
+    view! {
+        <nav class="series-nav not-prose" aria-label="連載">
+            <p class="series-nav-title">
+                "連載 "
+                <a href=index_url>{format!("「{}」", nav.id)}</a>
+                <span class="series-nav-position">
+                    {format!("全 {} 回中 第 {} 回", nav.total, nav.position)}
+                </span>
+            </p>
+            <div class="series-nav-links">
+                {nav.prev.map(|p| {
+                    let href = format!("/blog/{}", p.slug);
+                    view! { <a href=href class="series-nav-prev" rel="prev">{format!("← {}", p.title)}</a> }
+                })}
+                {nav.next.map(|n| {
+                    let href = format!("/blog/{}", n.slug);
+                    view! { <a href=href class="series-nav-next" rel="next">{format!("{} →", n.title)}</a> }
+                })}
+            </div>
+        </nav>
+    }
+}
+
+/// 記事の下に出す前後の記事と関連記事
+#[component]
+fn ReadNext(nav: PostNav) -> impl IntoView {
+    if nav.prev.is_none() && nav.next.is_none() && nav.related.is_empty() {
+        return None;
+    }
+    Some(view! {
+        <aside class="read-next not-prose" aria-label="次に読む">
+            {(nav.prev.is_some() || nav.next.is_some()).then(|| view! {
+                <nav class="read-next-adjacent" aria-label="前後の記事">
+                    {nav.prev.map(|p| view! {
+                        <div class="read-next-prev">
+                            <p class="read-next-label">"← 前の記事"</p>
+                            <PostCard post=p />
+                        </div>
+                    })}
+                    {nav.next.map(|n| view! {
+                        <div class="read-next-next">
+                            <p class="read-next-label">"次の記事 →"</p>
+                            <PostCard post=n />
+                        </div>
+                    })}
+                </nav>
+            })}
+            {(!nav.related.is_empty()).then(|| view! {
+                <section class="read-next-related">
+                    <h2>"関連記事"</h2>
+                    <div class="posts-list">
+                        {nav.related.into_iter().map(|post| view! { <PostCard post=post /> }).collect_view()}
+                    </div>
+                </section>
+            })}
+        </aside>
+    })
+}
+
 #[component]
 fn ShowTags(tags: Vec<String>) -> impl IntoView {
     let chips = tags
         .into_iter()
         .map(|t| {
+            let href = crate::frontmatter::tag_path(&t);
             view! {
-                <span class="blog-tag">
+                <a href=href class="blog-tag not-prose">
                     <span class="blog-tag-hash">"#"</span>
                     <span>{t}</span>
-                </span>
+                </a>
             }
         })
         .collect::<Vec<_>>();
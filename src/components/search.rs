@@ -2,19 +2,30 @@ use leptos::prelude::*;
 
 use super::HeaderBar;
 use super::MetaRow;
-use crate::app::render::SearchHit;
+use super::Pagination;
+use crate::app::config;
+use crate::app::render::{SearchFilterChip, SearchResults};
 
 #[component]
 pub fn SearchPage(
     client_ip: String,
     query: String,
-    results: Vec<SearchHit>,
+    results: SearchResults,
     current_path: String,
 ) -> impl IntoView {
+    let base_url = search_url(&query);
+    let SearchResults {
+        hits,
+        total,
+        current_page,
+        total_pages,
+        filters,
+        error,
+    } = results;
     view! {
         <div class="blog-wrapper">
             <HeaderBar
-                title="すずねーう".to_string()
+                title=config().site.name.clone()
                 subtitle=format!("{client_ip}")
                 current_path=current_path.clone()
             />
@@ -25,7 +36,7 @@ pub fn SearchPage(
                         <input
                             type="search"
                             name="q"
-                            placeholder="キーワードを入力"
+                            placeholder="キーワード（tag:rust \"語句\" -除外 A OR B）"
                             value=query.clone()
                         />
                         <button
@@ -34,22 +45,34 @@ pub fn SearchPage(
                             "検索"
                         </button>
                     </form>
+                    <FilterChips filters=filters />
                 </div>
 
                 <div>
                     {if query.is_empty() {
                         view! { <p class="search-error">"検索キーワードを入力してください"</p> }.into_any()
-                    } else if results.is_empty() {
+                    } else if let Some(error) = error {
+                        view! { <p class="search-error">{error}</p> }.into_any()
+                    } else if hits.is_empty() {
                         view! { <p class="search-error">"該当する記事が見つかりませんでした"</p> }.into_any()
                     } else {
                         view! {
+                            <p class="search-count">{format!("{total} 件")}</p>
                             <ul>
-                                {results
+                                {hits
                                     .into_iter()
                                     .map(|hit| {
                                         let url = format!("/blog/{}", hit.slug);
+                                        let section = hit.section.map(|section| {
+                                            let href = format!("{url}#{}", section.id);
+                                            view! {
+                                                <a href=href class="search-section">
+                                                    {format!("§ {}", section.title)}
+                                                </a>
+                                            }
+                                        });
                                         view! {
-                                            <li>
+                                            <li data-score=format!("{:.4}", hit.score)>
                                                 <a href=url.clone()>
                                                     {hit.title.clone()}
                                                 </a>
@@ -61,12 +84,18 @@ pub fn SearchPage(
                                                         slug=None
                                                     />
                                                 </div>
-                                                <p>{hit.snippet}</p>
+                                                {section}
+                                                <p inner_html=hit.snippet_html></p>
                                             </li>
                                         }
                                     })
                                     .collect_view()}
                             </ul>
+                            <Pagination
+                                current_page=current_page
+                                total_pages=total_pages
+                                base_url=base_url
+                            />
                         }
                         .into_any()
                     }}
@@ -75,3 +104,52 @@ pub fn SearchPage(
         </div>
     }
 }
+
+/// 検索式の中の絞り込み（押すとその項を外して検索し直す）
+#[component]
+fn FilterChips(filters: Vec<SearchFilterChip>) -> impl IntoView {
+    if filters.is_empty() {
+        return None;
+    }
+    Some(view! {
+        <div class="search-filters">
+            {filters
+                .into_iter()
+                .map(|chip| {
+                    let url = search_url(&chip.remaining_query);
+                    let label = format!("{} を外す", chip.label);
+                    view! {
+                        <a href=url class="search-chip" aria-label=label.clone() title=label>
+                            {chip.label}
+                            <span aria-hidden="true">"×"</span>
+                        </a>
+                    }
+                })
+                .collect_view()}
+        </div>
+    })
+}
+
+/// 式が空なら検索フォームだけのページ
+fn search_url(query: &str) -> String {
+    if query.is_empty() {
+        "/search".to_string()
+    } else {
+        format!("/search?q={}", encode_query(query))
+    }
+}
+
+/// `?q=` の値（空白は `+`）
+fn encode_query(query: &str) -> String {
+    let mut out = String::new();
+    for b in query.bytes() {
+        match b {
+            b' ' => out.push('+'),
+            b if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') => {
+                out.push(b as char)
+            }
+            b => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
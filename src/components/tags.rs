@@ -0,0 +1,45 @@
+use leptos::prelude::*;
+
+use super::HeaderBar;
+use crate::app::config;
+use crate::app::render::TagSummary;
+
+#[component]
+pub fn TagsPage(client_ip: String, tags: Vec<TagSummary>) -> impl IntoView {
+    view! {
+        <div class="blog-wrapper">
+            <HeaderBar
+                title=config().site.name.clone()
+                subtitle=format!("{client_ip}")
+                current_path="/tags".to_string()
+            />
+            <main class="blog-container">
+                <h1 class="blog-list-title">"タグ一覧"</h1>
+                {if tags.is_empty() {
+                    view! { <p>"タグの付いた記事はまだありません"</p> }.into_any()
+                } else {
+                    view! {
+                        <ul class="blog-tags tag-list">
+                            {tags
+                                .into_iter()
+                                .map(|tag| {
+                                    let href = crate::frontmatter::tag_path(&tag.name);
+                                    view! {
+                                        <li>
+                                            <a href=href class="blog-tag">
+                                                <span class="blog-tag-hash">"#"</span>
+                                                <span>{tag.name}</span>
+                                                <span class="tag-count">{format!("({})", tag.count)}</span>
+                                            </a>
+                                        </li>
+                                    }
+                                })
+                                .collect_view()}
+                        </ul>
+                    }
+                    .into_any()
+                }}
+            </main>
+        </div>
+    }
+}
@@ -0,0 +1,361 @@
+use chrono::FixedOffset;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, env, fmt, fs, net::IpAddr, path::PathBuf};
+
+/// Default location of the site configuration, relative to the working directory.
+pub const CONFIG_PATH: &str = "rodin.toml";
+/// Points the server, `build.rs` and `rodin-content` at a different configuration file.
+pub const CONFIG_PATH_ENV_KEY: &str = "RODIN_CONFIG";
+/// Overrides `site.url` (e.g. for staging builds).
+pub const SITE_URL_ENV_KEY: &str = "RODIN_SITE_URL";
+
+/// Typed contents of `rodin.toml`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SiteConfig {
+    pub site: Site,
+    #[serde(default)]
+    pub social: Vec<SocialLink>,
+    #[serde(default)]
+    pub csp: Csp,
+    /// Keys usable in a post's `breadcrumbs:` front matter.
+    #[serde(default)]
+    pub breadcrumbs: BTreeMap<String, Breadcrumb>,
+    #[serde(default)]
+    pub server: ServerConfig,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Site {
+    /// Absolute origin without a trailing slash.
+    pub url: String,
+    /// Short name used in headers and `<title>` suffixes.
+    pub name: String,
+    /// Title of the home page and the site-wide feeds.
+    pub title: String,
+    pub description: String,
+    pub og_site_name: String,
+    pub author: String,
+    #[serde(default)]
+    pub tagline: String,
+    /// `ja_JP` style; the part before `_` is used as the language tag.
+    pub locale: String,
+    /// Twitter/X handle including the leading `@`.
+    #[serde(default)]
+    pub twitter: Option<String>,
+    /// Avatar shown on the top page and used as the fallback article image.
+    pub avatar: String,
+    /// UTC offset such as `+09:00`; "today" for new posts and `before:`/`after:` in search use this zone.
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+}
+
+fn default_timezone() -> String {
+    "+00:00".to_string()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SocialLink {
+    /// `X`, `GitHub` or `Discord`; anything else gets a generic icon.
+    pub kind: String,
+    pub url: String,
+}
+
+/// Extra sources allowed by the Content-Security-Policy on top of `'self'`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Csp {
+    pub script_src: Vec<String>,
+    pub connect_src: Vec<String>,
+    pub frame_src: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Breadcrumb {
+    pub name: String,
+    pub path: String,
+}
+
+/// Runtime knobs; each one can be overridden by the environment variable named in `rodin.toml`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind_address: String,
+    pub port: u16,
+    pub trust_proxy: bool,
+    pub cache_enabled: bool,
+    pub compression_enabled: bool,
+    pub related_text_similarity: bool,
+    /// Lets open pages refresh themselves after `/__admin/reload` (dev only).
+    pub live_reload: bool,
+    pub log_file: PathBuf,
+    /// Seconds between access log flushes in production.
+    pub log_flush_interval: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: "0.0.0.0".to_string(),
+            port: 3000,
+            trust_proxy: false,
+            cache_enabled: false,
+            compression_enabled: true,
+            related_text_similarity: false,
+            live_reload: false,
+            log_file: PathBuf::from("logs/access.log"),
+            log_flush_interval: 5,
+        }
+    }
+}
+
+/// Everything wrong with the configuration, reported at once.
+#[derive(Debug)]
+pub struct ConfigError {
+    path: PathBuf,
+    problems: Vec<String>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid site configuration ({})", self.path.display())?;
+        for problem in &self.problems {
+            write!(f, "\n  - {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// `rodin.toml`, or the file named by `RODIN_CONFIG`.
+pub fn config_path() -> PathBuf {
+    env::var(CONFIG_PATH_ENV_KEY)
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .map_or_else(|| PathBuf::from(CONFIG_PATH), PathBuf::from)
+}
+
+impl SiteConfig {
+    /// Reads the configuration file, applies environment overrides and validates the result.
+    pub fn load() -> Result<Self, ConfigError> {
+        let path = config_path();
+        let fail = |problem: String| ConfigError {
+            path: path.clone(),
+            problems: vec![problem],
+        };
+        let text = fs::read_to_string(&path).map_err(|e| fail(format!("cannot read: {e}")))?;
+        let mut config: SiteConfig = toml::from_str(&text).map_err(|e| fail(e.to_string()))?;
+        let mut problems = config.apply_env();
+        problems.extend(config.validate());
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError { path, problems })
+        }
+    }
+
+    fn apply_env(&mut self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let text = |v: &str| Some(v.to_string());
+        override_from_env(&mut problems, SITE_URL_ENV_KEY, &mut self.site.url, text);
+        let server = &mut self.server;
+        override_from_env(
+            &mut problems,
+            "BIND_ADDRESS",
+            &mut server.bind_address,
+            text,
+        );
+        override_from_env(&mut problems, "PORT", &mut server.port, |v| v.parse().ok());
+        override_from_env(
+            &mut problems,
+            "TRUST_PROXY",
+            &mut server.trust_proxy,
+            parse_flag,
+        );
+        override_from_env(
+            &mut problems,
+            "CACHE_ENABLED",
+            &mut server.cache_enabled,
+            parse_flag,
+        );
+        override_from_env(
+            &mut problems,
+            "COMPRESSION_ENABLED",
+            &mut server.compression_enabled,
+            parse_flag,
+        );
+        override_from_env(
+            &mut problems,
+            "RELATED_TEXT_SIMILARITY",
+            &mut server.related_text_similarity,
+            parse_flag,
+        );
+        override_from_env(
+            &mut problems,
+            "LIVE_RELOAD",
+            &mut server.live_reload,
+            parse_flag,
+        );
+        override_from_env(&mut problems, "LOG_FILE", &mut server.log_file, |v| {
+            Some(PathBuf::from(v))
+        });
+        override_from_env(
+            &mut problems,
+            "LOG_FLUSH_INTERVAL",
+            &mut server.log_flush_interval,
+            |v| v.parse().ok(),
+        );
+        problems
+    }
+
+    fn validate(&mut self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let site = &mut self.site;
+        site.url = site.url.trim().trim_end_matches('/').to_string();
+        if !is_http_url(&site.url) {
+            problems.push(format!(
+                "site.url: expected an absolute http(s) URL, got `{}`",
+                site.url
+            ));
+        }
+        for (key, value) in [
+            ("site.name", &site.name),
+            ("site.title", &site.title),
+            ("site.description", &site.description),
+            ("site.og_site_name", &site.og_site_name),
+            ("site.author", &site.author),
+        ] {
+            if value.trim().is_empty() {
+                problems.push(format!("{key}: must not be empty"));
+            }
+        }
+        let (lang, region) = site.locale.split_once('_').unwrap_or((&site.locale, ""));
+        if !(2..=3).contains(&lang.len())
+            || !lang.chars().all(|c| c.is_ascii_lowercase())
+            || !(region.is_empty()
+                || region.len() == 2 && region.chars().all(|c| c.is_ascii_uppercase()))
+        {
+            problems.push(format!(
+                "site.locale: expected something like `ja_JP`, got `{}`",
+                site.locale
+            ));
+        }
+        if let Some(handle) = &site.twitter {
+            let name = handle.strip_prefix('@').unwrap_or_default();
+            if !(1..=15).contains(&name.len())
+                || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                problems.push(format!(
+                    "site.twitter: expected a handle like `@name`, got `{handle}`"
+                ));
+            }
+        }
+        if site.timezone.parse::<FixedOffset>().is_err() {
+            problems.push(format!(
+                "site.timezone: expected a UTC offset like `+09:00`, got `{}`",
+                site.timezone
+            ));
+        }
+        if !site.avatar.starts_with('/') && !is_http_url(&site.avatar) {
+            problems.push(format!(
+                "site.avatar: expected a path starting with `/` or a URL, got `{}`",
+                site.avatar
+            ));
+        }
+        for (i, link) in self.social.iter().enumerate() {
+            if !is_http_url(&link.url) {
+                problems.push(format!(
+                    "social[{i}].url: expected an absolute http(s) URL, got `{}`",
+                    link.url
+                ));
+            }
+        }
+        for (key, sources) in [
+            ("csp.script_src", &self.csp.script_src),
+            ("csp.connect_src", &self.csp.connect_src),
+            ("csp.frame_src", &self.csp.frame_src),
+        ] {
+            for source in sources {
+                if source.is_empty()
+                    || source
+                        .chars()
+                        .any(|c| c.is_whitespace() || matches!(c, ';' | ',' | '\'' | '"'))
+                {
+                    problems.push(format!("{key}: `{source}` is not a valid source"));
+                }
+            }
+        }
+        for (key, crumb) in &self.breadcrumbs {
+            if crumb.name.trim().is_empty() {
+                problems.push(format!("breadcrumbs.{key}.name: must not be empty"));
+            }
+            if !crumb.path.starts_with('/') {
+                problems.push(format!(
+                    "breadcrumbs.{key}.path: expected a path starting with `/`, got `{}`",
+                    crumb.path
+                ));
+            }
+        }
+        let server = &self.server;
+        if server.bind_address.parse::<IpAddr>().is_err() {
+            problems.push(format!(
+                "server.bind_address: expected an IP address, got `{}`",
+                server.bind_address
+            ));
+        }
+        if server.port == 0 {
+            problems.push("server.port: must not be 0".to_string());
+        }
+        if server.log_flush_interval == 0 {
+            problems.push("server.log_flush_interval: must be at least 1 second".to_string());
+        }
+        problems
+    }
+}
+
+impl Site {
+    /// Language tag for `<html lang>`, `inLanguage` and feeds (`ja_JP` -> `ja`).
+    pub fn lang(&self) -> &str {
+        self.locale.split('_').next().unwrap_or(&self.locale)
+    }
+
+    /// Profile URL for the configured Twitter/X handle.
+    pub fn twitter_url(&self) -> Option<String> {
+        self.twitter
+            .as_deref()
+            .map(|h| format!("https://x.com/{}", h.trim_start_matches('@')))
+    }
+}
+
+fn override_from_env<T>(
+    problems: &mut Vec<String>,
+    key: &str,
+    slot: &mut T,
+    parse: impl Fn(&str) -> Option<T>,
+) {
+    let Ok(raw) = env::var(key) else {
+        return;
+    };
+    match parse(raw.trim()) {
+        Some(value) => *slot = value,
+        None => problems.push(format!("{key}: cannot use `{raw}` from the environment")),
+    }
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "on" | "yes" => Some(true),
+        "0" | "false" | "off" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+fn is_http_url(url: &str) -> bool {
+    url.strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .is_some_and(|rest| !rest.is_empty() && !rest.contains(char::is_whitespace))
+}
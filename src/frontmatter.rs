@@ -1,5 +1,36 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf, str::FromStr};
+
+/// Draft output (HTML, `drafts.json`); kept out of `static/` so that only
+/// `/preview/{slug}` can read it.
+pub const PRIVATE_DIR: &str = "generated-private";
+
+/// Publication state set by `//: status:`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PostStatus {
+    /// Not built at all.
+    Draft,
+    /// Reachable by URL only; kept out of listings, search, sitemap and feeds.
+    Unlisted,
+    #[default]
+    Published,
+}
+
+impl FromStr for PostStatus {
+    type Err = ();
+
+    /// `draft`, `unlisted` or `published` (also `public`), case-insensitive.
+    fn from_str(value: &str) -> Result<Self, ()> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "draft" => Ok(Self::Draft),
+            "unlisted" => Ok(Self::Unlisted),
+            "published" | "public" => Ok(Self::Published),
+            _ => Err(()),
+        }
+    }
+}
 
 #[derive(Clone, Default, Serialize, Deserialize)]
 pub struct FrontMatter {
@@ -19,4 +50,123 @@ pub struct FrontMatter {
     pub markdown: Option<String>,
     #[serde(default)]
     pub reading_minutes: Option<u32>,
+    #[serde(default)]
+    pub status: PostStatus,
+    #[serde(default)]
+    pub publish_at: Option<String>,
+    /// Source file, e.g. `content/2025/foo.typ` or `content/foo/index.typ`.
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Series id set by `//: series:`; parts are ordered by `series_order`.
+    #[serde(default)]
+    pub series: Option<String>,
+    #[serde(default)]
+    pub series_order: Option<u32>,
+    /// `h2`–`h4` headings of the compiled post, nested by level.
+    #[serde(default)]
+    pub toc: Vec<TocEntry>,
+    /// Set by `//: toc: false`; ids and anchors are still added.
+    #[serde(default)]
+    pub hide_toc: bool,
+    /// Generated Open Graph card; unset when the post sets `meta>og:image`.
+    #[serde(default)]
+    pub og_image: Option<String>,
+    /// Print rendition beside the HTML, e.g. `generated/foo.pdf`.
+    #[serde(default)]
+    pub pdf: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TocEntry {
+    pub level: u8,
+    /// `id` of the heading element (link target `#id`).
+    pub id: String,
+    pub text: String,
+    #[serde(default)]
+    pub children: Vec<TocEntry>,
+}
+
+impl FrontMatter {
+    /// Whether the post may show up in listings (ignores `publish_at`).
+    pub fn is_listed(&self) -> bool {
+        self.status == PostStatus::Published
+    }
+
+    /// Compiled HTML on disk (`html` is relative to `static/`, or to [`PRIVATE_DIR`] for drafts).
+    pub fn html_file(&self) -> PathBuf {
+        let root = match self.status {
+            PostStatus::Draft => PRIVATE_DIR,
+            _ => "static",
+        };
+        PathBuf::from(root).join(&self.html)
+    }
+
+    /// Parsed `publish_at` (the build stores it as RFC 3339 with the site's offset).
+    pub fn publish_time(&self) -> Option<DateTime<FixedOffset>> {
+        self.publish_at.as_deref().and_then(parse_datetime)
+    }
+
+    /// Listed, and either unscheduled or already past `publish_at` at `now`.
+    pub fn is_listed_at(&self, now: DateTime<Utc>) -> bool {
+        self.is_listed() && self.publish_time().is_none_or(|t| t <= now)
+    }
+}
+
+/// Parse a front-matter date; date-only and offset-less values are treated as UTC.
+pub fn parse_datetime(value: &str) -> Option<DateTime<FixedOffset>> {
+    parse_datetime_in(value, FixedOffset::east_opt(0)?)
+}
+
+/// Like [`parse_datetime`], but date-only and offset-less values are read in `offset`.
+pub fn parse_datetime_in(value: &str, offset: FixedOffset) -> Option<DateTime<FixedOffset>> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt);
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M"))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S"))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M"))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })?;
+    naive.and_local_timezone(offset).single()
+}
+
+/// Whether `slug` can be served as `/blog/{slug}`: one non-empty path segment of letters,
+/// digits, `-` and `_` (a `.` would be read as the `.md`/`.pdf`/`.typ` rendition suffix).
+pub fn is_valid_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && slug
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Normalise a tag into the key used for `/tags/{tag}` lookups.
+pub fn tag_key(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+/// Build the percent-encoded `/tags/{tag}` path for a tag.
+pub fn tag_path(tag: &str) -> String {
+    encode_path(String::from("/tags/"), &tag_key(tag))
+}
+
+/// Build the percent-encoded `/series/{id}` path for a series.
+pub fn series_path(id: &str) -> String {
+    encode_path(String::from("/series/"), id.trim())
+}
+
+fn encode_path(mut out: String, segment: &str) -> String {
+    for b in segment.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
 }
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Where the build writes the responsive image manifest.
+pub const IMAGE_MANIFEST_PATH: &str = "static/generated/images/manifest.json";
+
+/// Public URL of the original (e.g. `/assets/images/foo.png`) -> generated variants.
+pub type ImageManifest = HashMap<String, ResponsiveImage>;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ResponsiveImage {
+    /// Hash of the original file; variants are regenerated when it changes.
+    pub hash: String,
+    pub width: u32,
+    pub height: u32,
+    /// Tiny blurred JPEG as a `data:` URL, shown until the real image loads.
+    pub lqip: String,
+    pub avif: Vec<ImageVariant>,
+    pub webp: Vec<ImageVariant>,
+    /// JPEG, or PNG when the original has transparency.
+    pub fallback: Vec<ImageVariant>,
+    pub fallback_type: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImageVariant {
+    pub width: u32,
+    pub url: String,
+}
+
+impl ResponsiveImage {
+    /// `srcset` value for one format, narrowest first.
+    pub fn srcset(variants: &[ImageVariant]) -> String {
+        variants
+            .iter()
+            .map(|v| format!("{} {}w", v.url, v.width))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Widest fallback variant, used as the plain `src`.
+    pub fn fallback_src(&self) -> Option<&str> {
+        self.fallback.last().map(|v| v.url.as_str())
+    }
+}
@@ -21,7 +21,7 @@ use tracing_subscriber::{
     EnvFilter, Layer,
 };
 
-use crate::app::get_client_ip;
+use crate::app::{config, get_client_ip};
 
 /// Environment: dev or prod
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -46,9 +46,7 @@ impl Environment {
 
 /// Log file path
 fn log_file_path() -> PathBuf {
-    env::var("LOG_FILE")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from("logs/access.log"))
+    config().server.log_file.clone()
 }
 
 /// Buffered file writer for production (batched writes)
@@ -239,12 +237,7 @@ fn init_dev(log_path: PathBuf) -> anyhow::Result<()> {
 
 /// Prod: Console (errors only) + File (batched write every 5 seconds)
 fn init_prod(log_path: PathBuf) -> anyhow::Result<()> {
-    let flush_interval = Duration::from_secs(
-        env::var("LOG_FLUSH_INTERVAL")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(5),
-    );
+    let flush_interval = Duration::from_secs(config().server.log_flush_interval);
 
     let file_writer = FileWriter::Buffered(BufferedFileWriter::new(log_path, flush_interval)?);
 
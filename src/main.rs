@@ -1,11 +1,20 @@
 mod app;
 mod asset;
+mod cards;
 mod components;
+mod config;
+#[path = "../build/feed.rs"]
+mod feed;
 mod frontmatter;
+mod images;
 mod logging;
+mod preview;
+#[path = "../build/sitemap.rs"]
+mod sitemap;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    app::init_config()?;
     logging::init()?;
     app::run().await
 }
@@ -0,0 +1,21 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Build a preview token (`{expires}.{hex hmac}`) for `/preview/{slug}?token=...`.
+///
+/// `expires` is a UNIX timestamp in seconds; the signature covers both the slug and
+/// the expiry so a token can neither be reused for another post nor extended.
+pub fn preview_token(secret: &str, slug: &str, expires: i64) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(slug.as_bytes());
+    mac.update(b":");
+    mac.update(expires.to_string().as_bytes());
+    let sig: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("{expires}.{sig}")
+}
@@ -70,7 +70,7 @@
       const parser = new DOMParser();
       // DOMParser also requires TrustedHTML in strict CSP environments
       const doc = parser.parseFromString(safeHTML(html), "text/html");
-      const title = doc.title || "すずねーう";
+      const title = doc.title || document.title;
       const bodyHtml = doc.body.innerHTML;
       
       // Extract stylesheet links from the new page
@@ -0,0 +1,29 @@
+// OG 画像（1200×630）のテンプレート
+// build/og.rs が og-title / og-subtitle / og-tags / og-date / og-author を先頭に差し込む
+
+#set page(width: 1200pt, height: 630pt, margin: (x: 80pt, y: 72pt), fill: rgb("#0f172a"))
+#set text(font: "IBM Plex Sans JP", fill: rgb("#f8fafc"), lang: "ja")
+
+#grid(
+  rows: (1fr, auto),
+  row-gutter: 24pt,
+  align(horizon)[
+    #block(text(size: 60pt, weight: "bold", og-title))
+    #if og-subtitle != "" {
+      v(12pt)
+      text(size: 32pt, fill: rgb("#cbd5e1"), og-subtitle)
+    }
+  ],
+  grid(
+    columns: (1fr, auto),
+    align: bottom,
+    {
+      set text(size: 24pt, fill: rgb("#93c5fd"))
+      og-tags.map(t => "#" + t).join("  ")
+    },
+    align(right, text(size: 24pt, fill: rgb("#cbd5e1"))[
+      #if og-date != "" [#og-date #h(16pt)]
+      #text(weight: "semibold", og-author)
+    ]),
+  ),
+)
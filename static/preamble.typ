@@ -3,11 +3,43 @@
 
 #set figure(numbering: none)
 #set raw(theme: "github-dark.tmTheme")
+// Markdown 書き出し用に数式を Typst の記法に戻す（よく使う要素だけ）
+#let math-source(it) = {
+  if type(it) == str { return it }
+  let f = it.func()
+  if it.has("text") {
+    it.text
+  } else if it.has("children") {
+    it.children.map(math-source).join()
+  } else if f == math.frac {
+    "(" + math-source(it.num) + ")/(" + math-source(it.denom) + ")"
+  } else if f == math.attach {
+    let out = math-source(it.base)
+    if it.has("b") and it.b != none { out += "_(" + math-source(it.b) + ")" }
+    if it.has("t") and it.t != none { out += "^(" + math-source(it.t) + ")" }
+    out
+  } else if f == math.root {
+    "sqrt(" + math-source(it.radicand) + ")"
+  } else if it.has("body") {
+    math-source(it.body)
+  } else if f == [ ].func() {
+    " "
+  } else {
+    ""
+  }
+}
+
 #show math.equation: it => context {
   if target() == "html" {
     // HTML で出ないのを直す
-    show: if it.block { it => it } else { box }
-    html.frame(it)
+    let frame = {
+      show: if it.block { it => it } else { box }
+      html.frame(it)
+    }
+    html.elem(
+      if it.block { "div" } else { "span" },
+      attrs: (class: "math", "data-math": math-source(it.body)),
+    )[#frame]
   } else {
     it
   }
@@ -60,7 +92,7 @@
   link(url)[#display]
 }
 
-#let img(src, alt: "", lazy: false, width: none, height: none) = context {
+#let img(src, alt: "", lazy: true, width: none, height: none) = context {
   if target() == "html" {
     html.elem("img", attrs: (
       src: src,
@@ -118,7 +150,7 @@
     block(
       fill: rgb("#fffbeb"),
       inset: 12pt,
-    )[body]
+    )[#body]
   }
 }
 
@@ -144,7 +176,7 @@
   let safe_date = if date == none { panic("twitterembed: date is required") } else { date }
 
   if target() != "html" {
-    blockquote(safe_body, attribution: safe_author + " — " + safe_date)
+    quote(block: true, attribution: safe_author + " — " + safe_date)[#safe_body]
   } else {
     let content = if type(safe_body) == str { html.raw(safe_body) } else { safe_body }
 
@@ -159,4 +191,5 @@
   }
 }
 
-#let twitter-link = "https://x.com/suzuneu_discord"
+// build/posts.rs が rodin.toml の site.twitter から定義し直す（未設定なら none）
+#let twitter-link = none
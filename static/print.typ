@@ -0,0 +1,37 @@
+// PDF 版（/blog/{slug}.pdf）の体裁
+// build/posts.rs がプリアンブルの後に post-title / post-subtitle / post-date / post-tags / post-url / post-author / site-name を定義してから読み込む
+
+#set document(title: post-title, author: post-author, keywords: post-tags)
+#set page(
+  paper: "a4",
+  margin: (x: 22mm, y: 24mm),
+  numbering: "1 / 1",
+  header: context {
+    if counter(page).get().first() > 1 {
+      set text(size: 8pt, fill: luma(110))
+      post-title
+      h(1fr)
+      site-name
+    }
+  },
+)
+#set text(font: "IBM Plex Sans JP", size: 10pt, lang: "ja")
+#set par(justify: true, leading: 0.85em)
+#show heading: set block(above: 1.6em, below: 0.8em)
+// プリアンブルは画面用のダークテーマなので紙では既定に戻す
+#set raw(theme: auto)
+#show raw.where(block: true): block.with(fill: luma(245), inset: 8pt, radius: 4pt, width: 100%)
+
+#block(below: 2em)[
+  #text(size: 20pt, weight: "bold", post-title)
+  #if post-subtitle != "" {
+    linebreak()
+    text(size: 12pt, fill: luma(80), post-subtitle)
+  }
+  #v(0.4em)
+  #set text(size: 9pt, fill: luma(100))
+  #if post-date != "" [#post-date #h(1em)]
+  #post-tags.map(t => "#" + t).join(" ")
+  #h(1fr)
+  #link(post-url)
+]